@@ -0,0 +1,331 @@
+use crate::unit::GlucoseUnit;
+use crate::GlucosReading;
+
+/// mg/dL limits outside of which a reading warrants an alert.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ClinicalThresholds {
+    pub urgent_low: i32,
+    pub low: i32,
+    pub high: i32,
+    pub urgent_high: i32,
+}
+
+impl Default for ClinicalThresholds {
+    fn default() -> Self {
+        Self {
+            urgent_low: 55,
+            low: 70,
+            high: 180,
+            urgent_high: 250,
+        }
+    }
+}
+
+impl ClinicalThresholds {
+    pub fn is_out_of_range(&self, reading: &GlucosReading) -> bool {
+        reading.value < self.low || reading.value > self.high
+    }
+}
+
+/// Whether `reading` is out of range and at least `snooze_secs` have passed
+/// since `last_alert_ms` (or no alert has fired yet).
+pub fn should_alert(
+    reading: &GlucosReading,
+    thresholds: &ClinicalThresholds,
+    last_alert_ms: Option<i64>,
+    snooze_secs: u32,
+    now_ms: i64,
+) -> bool {
+    if !thresholds.is_out_of_range(reading) {
+        return false;
+    }
+
+    match last_alert_ms {
+        None => true,
+        Some(last_alert_ms) => now_ms - last_alert_ms >= snooze_secs as i64 * 1000,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AlertKind {
+    UrgentLow,
+    Low,
+    High,
+    UrgentHigh,
+}
+
+impl AlertKind {
+    pub fn classify(reading: &GlucosReading, thresholds: &ClinicalThresholds) -> Option<Self> {
+        match reading.value {
+            value if value < thresholds.urgent_low => Some(AlertKind::UrgentLow),
+            value if value < thresholds.low => Some(AlertKind::Low),
+            value if value > thresholds.urgent_high => Some(AlertKind::UrgentHigh),
+            value if value > thresholds.high => Some(AlertKind::High),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            AlertKind::UrgentLow => "Urgent low",
+            AlertKind::Low => "Low",
+            AlertKind::High => "High",
+            AlertKind::UrgentHigh => "Urgent high",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GlucoseAlert {
+    pub reading: GlucosReading,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+/// Delivery side of alerting (push notification, buzzer, LED, ...).
+pub trait AlertSink {
+    fn fire(&mut self, alert: GlucoseAlert);
+}
+
+/// `AlertSink` that writes alerts to the `log` crate at warn level.
+#[cfg(feature = "log")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogSink;
+
+#[cfg(feature = "log")]
+impl AlertSink for LogSink {
+    fn fire(&mut self, alert: GlucoseAlert) {
+        log::warn!("{}", alert.message);
+    }
+}
+
+/// Runs `should_alert` on each reading and fires `sink` when it passes,
+/// remembering the last alert time for the snooze.
+pub struct AlertMonitor<S: AlertSink> {
+    pub thresholds: ClinicalThresholds,
+    pub snooze_secs: u32,
+    last_alert_ms: Option<i64>,
+    sink: S,
+}
+
+impl<S: AlertSink> AlertMonitor<S> {
+    pub fn new(thresholds: ClinicalThresholds, snooze_secs: u32, sink: S) -> Self {
+        Self {
+            thresholds,
+            snooze_secs,
+            last_alert_ms: None,
+            sink,
+        }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns whether an alert was fired.
+    pub fn check(&mut self, reading: &GlucosReading, now_ms: i64) -> bool {
+        if !should_alert(
+            reading,
+            &self.thresholds,
+            self.last_alert_ms,
+            self.snooze_secs,
+            now_ms,
+        ) {
+            return false;
+        }
+
+        let Some(kind) = AlertKind::classify(reading, &self.thresholds) else {
+            return false;
+        };
+
+        self.last_alert_ms = Some(now_ms);
+        self.sink.fire(GlucoseAlert {
+            reading: *reading,
+            kind,
+            message: format!(
+                "{} glucose: {} mg/dL {}",
+                kind.label(),
+                reading.value,
+                reading.trend.arrow()
+            ),
+        });
+        true
+    }
+}
+
+/// Notification text for the change from `prev` to `curr` in `unit`, e.g.
+/// `Glucose rose from 120 to 145 mg/dL (+25) ↑`. Values clamped at the
+/// sensor limits read `LOW` or `HIGH` and then no delta is given.
+pub fn describe_change(prev: &GlucosReading, curr: &GlucosReading, unit: GlucoseUnit) -> String {
+    let arrow = match curr.trend.arrow() {
+        "" => String::new(),
+        arrow => format!(" {}", arrow),
+    };
+
+    if prev.value == curr.value {
+        return format!("Glucose unchanged at {}{}", level(curr, unit, true), arrow);
+    }
+
+    let verb = if curr.value > prev.value {
+        "rose"
+    } else {
+        "fell"
+    };
+    let in_range = |reading: &GlucosReading| !reading.is_below_range() && !reading.is_above_range();
+
+    if in_range(prev) && in_range(curr) {
+        let delta = match unit {
+            GlucoseUnit::MgDl => format!("{:+}", curr.value - prev.value),
+            GlucoseUnit::MmolL => format!("{:+.1}", curr.value_in(unit) - prev.value_in(unit)),
+        };
+        format!(
+            "Glucose {} from {} to {} ({}){}",
+            verb,
+            level(prev, unit, false),
+            level(curr, unit, true),
+            delta,
+            arrow
+        )
+    } else {
+        format!(
+            "Glucose {} from {} to {}{}",
+            verb,
+            level(prev, unit, true),
+            level(curr, unit, true),
+            arrow
+        )
+    }
+}
+
+fn level(reading: &GlucosReading, unit: GlucoseUnit, with_unit: bool) -> String {
+    let value = match unit {
+        _ if reading.is_below_range() => return "LOW".to_owned(),
+        _ if reading.is_above_range() => return "HIGH".to_owned(),
+        GlucoseUnit::MgDl => reading.value.to_string(),
+        GlucoseUnit::MmolL => format!("{:.1}", reading.value_in(unit)),
+    };
+
+    if with_unit {
+        format!("{} {}", value, unit)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::reading;
+    use crate::Trend;
+
+    #[test]
+    fn test_should_alert_respects_snooze() {
+        let thresholds = ClinicalThresholds::default();
+        let low = reading(62);
+        let now = 1699110415000;
+
+        assert!(should_alert(&low, &thresholds, None, 1800, now));
+        assert!(!should_alert(
+            &low,
+            &thresholds,
+            Some(now - 600_000),
+            1800,
+            now
+        ));
+        assert!(should_alert(
+            &low,
+            &thresholds,
+            Some(now - 1_800_000),
+            1800,
+            now
+        ));
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(Vec<GlucoseAlert>);
+
+    impl AlertSink for RecordingSink {
+        fn fire(&mut self, alert: GlucoseAlert) {
+            self.0.push(alert);
+        }
+    }
+
+    #[test]
+    fn test_monitor_fires_for_low_reading() {
+        let mut monitor = AlertMonitor::new(
+            ClinicalThresholds::default(),
+            1800,
+            RecordingSink::default(),
+        );
+
+        assert!(!monitor.check(&reading(120), 0));
+        assert!(monitor.check(&reading(62), 300_000));
+        assert!(!monitor.check(&reading(60), 600_000));
+
+        let alerts = &monitor.sink().0;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::Low);
+        assert_eq!(alerts[0].reading.value, 62);
+        assert_eq!(alerts[0].message, "Low glucose: 62 mg/dL →");
+    }
+
+    #[test]
+    fn test_should_alert_in_range() {
+        assert!(!should_alert(
+            &reading(120),
+            &ClinicalThresholds::default(),
+            None,
+            0,
+            0
+        ));
+    }
+
+    #[test]
+    fn test_describe_change() {
+        let at = |value, trend| GlucosReading {
+            trend,
+            ..reading(value)
+        };
+
+        assert_eq!(
+            describe_change(
+                &at(120, Trend::Flat),
+                &at(145, Trend::SingleUp),
+                GlucoseUnit::MgDl
+            ),
+            "Glucose rose from 120 to 145 mg/dL (+25) ↑"
+        );
+        assert_eq!(
+            describe_change(
+                &at(144, Trend::Flat),
+                &at(120, Trend::FortyFiveDown),
+                GlucoseUnit::MmolL
+            ),
+            "Glucose fell from 8.0 to 6.7 mmol/L (-1.3) ↘"
+        );
+        assert_eq!(
+            describe_change(
+                &at(120, Trend::Flat),
+                &at(120, Trend::Flat),
+                GlucoseUnit::MgDl
+            ),
+            "Glucose unchanged at 120 mg/dL →"
+        );
+        assert_eq!(
+            describe_change(
+                &at(380, Trend::DoubleUp),
+                &at(401, Trend::DoubleUp),
+                GlucoseUnit::MgDl
+            ),
+            "Glucose rose from 380 mg/dL to HIGH ↑↑"
+        );
+        assert_eq!(
+            describe_change(
+                &at(60, Trend::SingleDown),
+                &at(40, Trend::DoubleDown),
+                GlucoseUnit::MgDl
+            ),
+            "Glucose fell from 60 mg/dL to LOW ↓↓"
+        );
+    }
+}
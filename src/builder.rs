@@ -0,0 +1,190 @@
+use crate::client::Client;
+use crate::{url, Dexcom, Region};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BuildError {
+    EmptyApplicationId,
+    EmptyUserAgent,
+    ZeroBufferSize,
+    /// The base URL isn't an `https://host[/path]` URL, or has a query or
+    /// fragment.
+    InvalidBaseUrl,
+    /// The base URL uses plain `http`.
+    InsecureBaseUrl,
+}
+
+/// Fluent configuration for `Dexcom`. Options left unset keep the defaults
+/// used by `Dexcom::new`.
+///
+/// ```ignore
+/// let dexcom = DexcomBuilder::new(&mut client)
+///     .region(Region::Ous)
+///     .user_agent("my-app/1.0")
+///     .build()?;
+/// ```
+pub struct DexcomBuilder<'a, C: Client> {
+    dexcom: Dexcom<'a, C>,
+}
+
+impl<'a, C: Client> DexcomBuilder<'a, C> {
+    pub fn new(client: &'a mut C) -> Self {
+        Self {
+            dexcom: Dexcom::new(client),
+        }
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.dexcom.region = region;
+        self
+    }
+
+    pub fn auto_region(mut self, auto_region: bool) -> Self {
+        self.dexcom.auto_region = auto_region;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.dexcom.user_agent = user_agent.to_owned();
+        self
+    }
+
+    pub fn application_id(mut self, application_id: &str) -> Self {
+        self.dexcom.application_id = application_id.to_owned();
+        self
+    }
+
+    /// Size of the response buffer handed to the client on every request.
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.dexcom.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sends every request to `base_url` (e.g. a proxy) instead of the Share
+    /// host of the region, keeping the endpoint paths. Trailing slashes are
+    /// ignored; validation happens in `build`.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.dexcom.base_url = Some(base_url.to_owned());
+        self
+    }
+
+    pub fn request_id(mut self, request_id: &str) -> Self {
+        self.dexcom.request_id = Some(request_id.to_owned());
+        self
+    }
+
+    pub fn build(mut self) -> Result<Dexcom<'a, C>, BuildError> {
+        if self.dexcom.application_id.is_empty() {
+            return Err(BuildError::EmptyApplicationId);
+        }
+        if self.dexcom.user_agent.is_empty() {
+            return Err(BuildError::EmptyUserAgent);
+        }
+        if self.dexcom.buffer_size == 0 {
+            return Err(BuildError::ZeroBufferSize);
+        }
+        if let Some(base_url) = &self.dexcom.base_url {
+            self.dexcom.base_url = Some(url::normalize_base_url(base_url)?);
+        }
+
+        Ok(self.dexcom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_client;
+    use crate::DEFAULT_USER_AGENT;
+
+    #[test]
+    fn test_build() {
+        let mut client = mock_client();
+
+        let dexcom = DexcomBuilder::new(&mut client)
+            .region(Region::Ous)
+            .user_agent("my-app/1.0")
+            .application_id("00000000-0000-0000-0000-000000000000")
+            .buffer_size(2048)
+            .build()
+            .unwrap();
+
+        assert_eq!(dexcom.region(), Region::Ous);
+        assert_eq!(dexcom.user_agent, "my-app/1.0");
+        assert_eq!(
+            dexcom.application_id(),
+            "00000000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(dexcom.buffer_size, 2048);
+    }
+
+    #[test]
+    fn test_build_defaults() {
+        let mut client = mock_client();
+
+        let dexcom = DexcomBuilder::new(&mut client).build().unwrap();
+
+        assert_eq!(dexcom.region(), Region::default());
+        assert_eq!(dexcom.user_agent, DEFAULT_USER_AGENT);
+    }
+
+    #[test]
+    fn test_build_rejects_conflicting_options() {
+        let mut client = mock_client();
+        assert_eq!(
+            DexcomBuilder::new(&mut client)
+                .application_id("")
+                .build()
+                .err(),
+            Some(BuildError::EmptyApplicationId)
+        );
+
+        let mut client = mock_client();
+        assert_eq!(
+            DexcomBuilder::new(&mut client).buffer_size(0).build().err(),
+            Some(BuildError::ZeroBufferSize)
+        );
+    }
+
+    #[test]
+    fn test_base_url() {
+        let mut client = mock_client();
+        let dexcom = DexcomBuilder::new(&mut client)
+            .base_url("https://proxy.example.com/dexcom")
+            .build()
+            .unwrap();
+        assert_eq!(
+            dexcom.endpoints().login,
+            "https://proxy.example.com/dexcom/ShareWebServices/Services/General/LoginPublisherAccountById"
+        );
+
+        let mut client = mock_client();
+        let dexcom = DexcomBuilder::new(&mut client)
+            .region(Region::Ous)
+            .base_url("https://proxy.example.com//")
+            .build()
+            .unwrap();
+        assert_eq!(
+            dexcom.endpoints().glucose_readings,
+            "https://proxy.example.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues"
+        );
+
+        for (base_url, error) in [
+            ("http://proxy.example.com", BuildError::InsecureBaseUrl),
+            ("https://proxy.example.com/?a=b", BuildError::InvalidBaseUrl),
+            ("https://proxy.example.com#top", BuildError::InvalidBaseUrl),
+            ("https://", BuildError::InvalidBaseUrl),
+            ("proxy.example.com", BuildError::InvalidBaseUrl),
+        ] {
+            let mut client = mock_client();
+            assert_eq!(
+                DexcomBuilder::new(&mut client)
+                    .base_url(base_url)
+                    .build()
+                    .err(),
+                Some(error),
+                "{}",
+                base_url
+            );
+        }
+    }
+}
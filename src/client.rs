@@ -1,9 +1,39 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, PoisonError};
+
 use embedded_svc::io::Error;
 #[cfg(test)]
 use mockall::automock;
 
+#[cfg(feature = "test-util")]
+mod canned;
+
+#[cfg(feature = "tls-pinning")]
+mod pinned;
+
+#[cfg(feature = "test-util")]
+mod replay;
+
+#[cfg(feature = "reqwest")]
+mod reqwest;
+
+#[cfg(feature = "ureq")]
+mod ureq;
+
+#[cfg(feature = "test-util")]
+pub use self::canned::{CannedClient, CannedError};
+#[cfg(feature = "tls-pinning")]
+pub use self::pinned::SpkiPin;
+#[cfg(feature = "test-util")]
+pub use self::replay::ReplayClient;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest::{ReqwestClient, ReqwestError};
+#[cfg(feature = "ureq")]
+pub use self::ureq::{UreqClient, UreqError};
+
 #[cfg(test)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct TestError;
 
 #[cfg(test)]
@@ -13,6 +43,20 @@ impl Error for TestError {
     }
 }
 
+/// Outcome of a request: how many bytes of the body were written into the
+/// caller's buffer, the HTTP status code, and the `Location` header of a 3xx
+/// redirect. Clients that follow redirects themselves, or can't see headers,
+/// leave `location` empty and redirects stay plain status errors.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HttpResponse {
+    pub body_len: usize,
+    pub status: u16,
+    pub location: Option<String>,
+}
+
+/// `body` is the serialized JSON request, borrowed only for the duration of
+/// the call; implementations that need an owned body (e.g. `bytes::Bytes`)
+/// copy it. The response body is written into `buf`.
 #[cfg_attr(test, automock(type Error=TestError;))]
 pub trait Client {
     type Error: Error;
@@ -23,5 +67,76 @@ pub trait Client {
         headers: &'a [(&'a str, &'a str)],
         body: &[u8],
         buf: &mut [u8],
-    ) -> Result<(usize, u16), Self::Error>;
+    ) -> Result<HttpResponse, Self::Error>;
+
+    /// Called before a run of requests to the same host (the authenticate and
+    /// login round trips), so pooling clients can keep one connection open.
+    fn begin_session(&mut self) {}
+
+    /// Ends the run started by `begin_session`, even when a request failed.
+    fn end_session(&mut self) {}
+}
+
+/// Shares one client between several `Dexcom`s on the same thread; each
+/// request borrows it for the duration of the call.
+impl<C: Client> Client for Rc<RefCell<C>> {
+    type Error = C::Error;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        headers: &'a [(&'a str, &'a str)],
+        body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        self.borrow_mut().post_request(uri, headers, body, buf)
+    }
+
+    fn begin_session(&mut self) {
+        self.borrow_mut().begin_session()
+    }
+
+    fn end_session(&mut self) {
+        self.borrow_mut().end_session()
+    }
+}
+
+/// Shares one client across threads; each request holds the lock for the
+/// duration of the call. A poisoned lock is taken over as is.
+impl<C: Client> Client for Arc<Mutex<C>> {
+    type Error = C::Error;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        headers: &'a [(&'a str, &'a str)],
+        body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .post_request(uri, headers, body, buf)
+    }
+
+    fn begin_session(&mut self) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .begin_session()
+    }
+
+    fn end_session(&mut self) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .end_session()
+    }
+}
+
+/// A `MockClient` that tolerates any number of `begin_session` /
+/// `end_session` calls, for tests that don't care about them.
+#[cfg(test)]
+pub fn mock_client() -> MockClient {
+    let mut client = MockClient::new();
+    client.expect_begin_session().returning(|| ());
+    client.expect_end_session().returning(|| ());
+    client
 }
@@ -0,0 +1,79 @@
+use embedded_svc::io::ErrorKind;
+
+use super::{Client, HttpResponse};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CannedError {
+    NoResponse(String),
+}
+
+impl embedded_svc::io::Error for CannedError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// In-memory `Client` answering requests from canned `(status, body)`
+/// responses, for deterministic tests without a live Dexcom account.
+///
+/// A response is registered against a URI suffix such as
+/// `/General/AuthenticatePublisherAccount`, so the same canned client works
+/// for either region; any query string is ignored when matching. Later
+/// registrations take precedence over earlier ones.
+#[derive(Debug, Default, Clone)]
+pub struct CannedClient {
+    responses: Vec<(String, u16, String)>,
+    requests: Vec<String>,
+}
+
+impl CannedClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response(mut self, uri: &str, status: u16, body: &str) -> Self {
+        self.respond(uri, status, body);
+        self
+    }
+
+    pub fn respond(&mut self, uri: &str, status: u16, body: &str) {
+        self.responses
+            .push((uri.to_owned(), status, body.to_owned()));
+    }
+
+    /// URIs requested so far, oldest first.
+    pub fn requests(&self) -> &[String] {
+        &self.requests
+    }
+}
+
+impl Client for CannedClient {
+    type Error = CannedError;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        _headers: &'a [(&'a str, &'a str)],
+        _body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        self.requests.push(uri.to_owned());
+
+        let path = uri.split('?').next().unwrap_or(uri);
+        let (_, status, body) = self
+            .responses
+            .iter()
+            .rev()
+            .find(|(suffix, _, _)| path.ends_with(suffix.as_str()))
+            .ok_or_else(|| CannedError::NoResponse(uri.to_owned()))?;
+
+        let body_len = body.len().min(buf.len());
+        buf[..body_len].copy_from_slice(&body.as_bytes()[..body_len]);
+
+        Ok(HttpResponse {
+            body_len,
+            status: *status,
+            location: None,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use ring::digest::{digest, SHA256};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, RootCertStore, SignatureScheme};
+
+use super::UreqClient;
+
+/// SHA-256 of a certificate's DER-encoded SubjectPublicKeyInfo, as printed by
+/// `openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256`.
+pub type SpkiPin = [u8; 32];
+
+/// Verifies the chain against the webpki roots as usual and additionally
+/// requires the leaf certificate's public key to match one of `pins`.
+#[derive(Debug)]
+struct PinnedVerifier {
+    pins: Vec<SpkiPin>,
+    inner: Arc<WebPkiServerVerifier>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let cert = webpki::EndEntityCert::try_from(end_entity)
+            .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        let hash = digest(&SHA256, cert.subject_public_key_info().as_ref());
+
+        if !self.pins.iter().any(|pin| pin[..] == *hash.as_ref()) {
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+
+        self.inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn pinned_verifier(pins: &[SpkiPin]) -> PinnedVerifier {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let inner = WebPkiServerVerifier::builder_with_provider(
+        Arc::new(roots),
+        Arc::new(rustls::crypto::ring::default_provider()),
+    )
+    .build()
+    .expect("webpki roots are not empty");
+
+    PinnedVerifier {
+        pins: pins.to_vec(),
+        inner,
+    }
+}
+
+impl UreqClient {
+    /// A `UreqClient` that only talks to servers whose leaf certificate's
+    /// public key matches one of `pins`, on top of the usual chain checks.
+    /// A mismatch fails the handshake and surfaces as a connection error.
+    ///
+    /// No default pins are shipped: Dexcom rotates its certificates, and
+    /// stale pins would lock every client out. Compute them from the
+    /// certificates currently served by the Share hosts you talk to, and pin
+    /// a backup key as well.
+    ///
+    /// Panics if `pins` is empty, which would lock out every host.
+    pub fn with_pinned_spki(pins: &[SpkiPin]) -> Self {
+        assert!(!pins.is_empty(), "with_pinned_spki needs at least one pin");
+
+        let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(pinned_verifier(pins)))
+        .with_no_client_auth();
+
+        Self::with_agent(
+            ureq::AgentBuilder::new()
+                .tls_config(Arc::new(config))
+                .build(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use rustls::pki_types::PrivateKeyDer;
+
+    use super::*;
+    use crate::client::{Client, UreqError};
+
+    fn self_signed() -> (CertificateDer<'static>, PrivateKeyDer<'static>, SpkiPin) {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["localhost".to_owned()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+
+        let mut pin = [0; 32];
+        pin.copy_from_slice(digest(&SHA256, &key.public_key_der()).as_ref());
+
+        (
+            cert.der().clone(),
+            PrivateKeyDer::try_from(key.serialize_der()).unwrap(),
+            pin,
+        )
+    }
+
+    #[test]
+    fn test_pin_mismatch_is_rejected() {
+        let (cert, _, pin) = self_signed();
+        let server_name = ServerName::try_from("localhost").unwrap();
+
+        let verifier = pinned_verifier(&[[0; 32]]);
+        assert_eq!(
+            verifier
+                .verify_server_cert(&cert, &[], &server_name, &[], UnixTime::now())
+                .unwrap_err(),
+            rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure)
+        );
+
+        // A matching pin doesn't bypass chain validation.
+        let verifier = pinned_verifier(&[pin]);
+        assert_eq!(
+            verifier
+                .verify_server_cert(&cert, &[], &server_name, &[], UnixTime::now())
+                .unwrap_err(),
+            rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pin")]
+    fn test_empty_pins_are_refused() {
+        UreqClient::with_pinned_spki(&[]);
+    }
+
+    #[test]
+    fn test_self_signed_server_is_rejected() {
+        let (cert, key, _) = self_signed();
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut connection = rustls::ServerConnection::new(Arc::new(config)).unwrap();
+            while connection.is_handshaking() {
+                if connection.complete_io(&mut stream).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = UreqClient::with_pinned_spki(&[[0; 32]]);
+        let mut buf = [0; 512];
+        let uri = format!("https://localhost:{}/", port);
+
+        assert!(matches!(
+            client.post_request(&uri, &[], b"{}", &mut buf),
+            Err(UreqError::Transport(_))
+        ));
+    }
+}
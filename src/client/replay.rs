@@ -0,0 +1,66 @@
+use super::{CannedError, Client, HttpResponse};
+
+const ACCOUNT_ID: &str = r#""5f3c3a2e-1d7b-4c8e-9a6f-0b2d4e6f8a1c""#;
+const SESSION_ID: &str = r#""9b1e7d4c-2a6f-4e3b-8c5d-7f0a1b2c3d4e""#;
+
+/// Offline `Client` that replays recorded glucose responses, for demos and
+/// UI work without a Dexcom account.
+///
+/// Authenticating and logging in always succeed with fixed ids. Each
+/// latest-glucose request answers with the next of the recorded bodies,
+/// starting over after the last one; with no bodies it answers `[]`.
+/// Any other endpoint fails with `CannedError::NoResponse`.
+#[derive(Debug, Default, Clone)]
+pub struct ReplayClient {
+    bodies: Vec<String>,
+    next: usize,
+}
+
+impl ReplayClient {
+    pub fn new<S: Into<String>>(bodies: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            bodies: bodies.into_iter().map(Into::into).collect(),
+            next: 0,
+        }
+    }
+}
+
+impl Client for ReplayClient {
+    type Error = CannedError;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        _headers: &'a [(&'a str, &'a str)],
+        _body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        let path = uri.split('?').next().unwrap_or(uri);
+        let body = if path.ends_with("/AuthenticatePublisherAccount") {
+            ACCOUNT_ID
+        } else if path.ends_with("/LoginPublisherAccountById")
+            || path.ends_with("/LoginPublisherAccountByName")
+        {
+            SESSION_ID
+        } else if path.ends_with("/ReadPublisherLatestGlucoseValues") {
+            match self.bodies.get(self.next) {
+                Some(body) => {
+                    self.next = (self.next + 1) % self.bodies.len();
+                    body.as_str()
+                }
+                None => "[]",
+            }
+        } else {
+            return Err(CannedError::NoResponse(uri.to_owned()));
+        };
+
+        let body_len = body.len().min(buf.len());
+        buf[..body_len].copy_from_slice(&body.as_bytes()[..body_len]);
+
+        Ok(HttpResponse {
+            body_len,
+            status: 200,
+            location: None,
+        })
+    }
+}
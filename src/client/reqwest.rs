@@ -0,0 +1,79 @@
+use embedded_svc::io::ErrorKind;
+
+use super::{Client, HttpResponse};
+
+#[derive(Debug)]
+pub struct ReqwestError(pub reqwest::Error);
+
+impl embedded_svc::io::Error for ReqwestError {
+    fn kind(&self) -> ErrorKind {
+        if self.0.is_timeout() {
+            ErrorKind::TimedOut
+        } else {
+            ErrorKind::Other
+        }
+    }
+}
+
+impl From<reqwest::Error> for ReqwestError {
+    fn from(value: reqwest::Error) -> Self {
+        ReqwestError(value)
+    }
+}
+
+/// `Client` implementation over `reqwest::blocking` for desktop targets.
+///
+/// Response bodies larger than the buffer passed by `Dexcom` are truncated.
+/// Redirects reach `Dexcom` as `ClientError::Redirect` only when the inner
+/// client doesn't follow them, i.e. with `redirect::Policy::none()`.
+#[derive(Default)]
+pub struct ReqwestClient {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_client(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Client for ReqwestClient {
+    type Error = ReqwestError;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        headers: &'a [(&'a str, &'a str)],
+        body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        let mut request = self.client.post(uri).body(body.to_vec());
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = request.send()?;
+        let status_code = response.status().as_u16();
+        let location = response
+            .status()
+            .is_redirection()
+            .then(|| response.headers().get(reqwest::header::LOCATION))
+            .flatten()
+            .and_then(|location| location.to_str().ok())
+            .map(str::to_owned);
+        let bytes = response.bytes()?;
+
+        let size = bytes.len().min(buf.len());
+        buf[..size].copy_from_slice(&bytes[..size]);
+
+        Ok(HttpResponse {
+            body_len: size,
+            status: status_code,
+            location,
+        })
+    }
+}
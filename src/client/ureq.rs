@@ -0,0 +1,154 @@
+use std::io::Read;
+
+use embedded_svc::io::ErrorKind;
+
+use super::{Client, HttpResponse};
+
+#[derive(Debug)]
+pub enum UreqError {
+    Transport(ureq::Transport),
+    Io(std::io::Error),
+}
+
+impl embedded_svc::io::Error for UreqError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            UreqError::Io(error) if error.kind() == std::io::ErrorKind::TimedOut => {
+                ErrorKind::TimedOut
+            }
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<std::io::Error> for UreqError {
+    fn from(value: std::io::Error) -> Self {
+        UreqError::Io(value)
+    }
+}
+
+/// `Client` implementation over `ureq`.
+///
+/// `ureq` reports 4xx/5xx responses as `ureq::Error::Status`; their bodies are
+/// still read into the buffer so `Dexcom` can decode the Dexcom error payload.
+/// Response bodies larger than the buffer are truncated. Redirects reach
+/// `Dexcom` as `ClientError::Redirect` only when the agent doesn't follow
+/// them, i.e. with `AgentBuilder::redirects(0)`.
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+impl UreqClient {
+    pub fn new() -> Self {
+        Self::with_agent(ureq::Agent::new())
+    }
+
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        Self { agent }
+    }
+}
+
+impl Default for UreqClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client for UreqClient {
+    type Error = UreqError;
+
+    fn post_request<'a>(
+        &mut self,
+        uri: &'a str,
+        headers: &'a [(&'a str, &'a str)],
+        body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, Self::Error> {
+        let mut request = self.agent.post(uri);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+
+        let response = match request.send_bytes(body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(transport)) => return Err(UreqError::Transport(transport)),
+        };
+
+        let status_code = response.status();
+        let location = (300..=399)
+            .contains(&status_code)
+            .then(|| response.header("Location").map(str::to_owned))
+            .flatten();
+        let mut reader = response.into_reader();
+
+        let mut size = 0;
+        while size < buf.len() {
+            match reader.read(&mut buf[size..])? {
+                0 => break,
+                read => size += read,
+            }
+        }
+
+        Ok(HttpResponse {
+            body_len: size,
+            status: status_code,
+            location,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        format!("http://{}/", address)
+    }
+
+    #[test]
+    fn test_error_status_body_is_read() {
+        let body = r#"{"Code":"SessionIdNotFound"}"#;
+        let uri = serve_once(
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Type: application/json\r\nContent-Length: 28\r\nConnection: close\r\n\r\n{\"Code\":\"SessionIdNotFound\"}",
+        );
+
+        let mut client = UreqClient::new();
+        let mut buf = [0; 512];
+        let response = client.post_request(&uri, &[], b"{}", &mut buf).unwrap();
+
+        assert_eq!(response.status, 500);
+        assert_eq!(&buf[..response.body_len], body.as_bytes());
+    }
+
+    #[test]
+    fn test_redirect_location_is_kept() {
+        let uri = serve_once(
+            "HTTP/1.1 302 Found\r\nLocation: https://shareous1.dexcom.com/\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+
+        let mut client = UreqClient::with_agent(ureq::AgentBuilder::new().redirects(0).build());
+        let mut buf = [0; 512];
+        let response = client.post_request(&uri, &[], b"{}", &mut buf).unwrap();
+
+        assert_eq!(response.status, 302);
+        assert_eq!(
+            response.location.as_deref(),
+            Some("https://shareous1.dexcom.com/")
+        );
+    }
+}
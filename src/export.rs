@@ -0,0 +1,77 @@
+use crate::timestamp::to_iso8601;
+use crate::GlucosReading;
+
+/// Renders a reading in the Prometheus text exposition format.
+pub fn to_prometheus(reading: &GlucosReading, now_ms: i64) -> String {
+    let age_seconds = (now_ms - reading.wall_time).max(0) / 1000;
+
+    format!(
+        "# TYPE glucose_mgdl gauge\n\
+         glucose_mgdl {}\n\
+         # TYPE glucose_trend gauge\n\
+         glucose_trend {}\n\
+         # TYPE glucose_age_seconds gauge\n\
+         glucose_age_seconds {}\n",
+        reading.value, reading.trend as u8, age_seconds
+    )
+}
+
+/// Renders readings as CSV with a `timestamp,value,trend` header, one row
+/// per reading in the given order. The timestamp is the wall time in ISO
+/// 8601 UTC and the trend its PascalCase name.
+pub fn readings_to_csv(readings: &[GlucosReading]) -> String {
+    let mut csv = String::from("timestamp,value,trend\n");
+    for reading in readings {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            to_iso8601(reading.wall_time),
+            reading.value,
+            reading.trend.name()
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Source, Trend};
+
+    #[test]
+    fn test_to_prometheus() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        assert_eq!(
+            to_prometheus(&reading, 1699110535000),
+            "# TYPE glucose_mgdl gauge\n\
+             glucose_mgdl 153\n\
+             # TYPE glucose_trend gauge\n\
+             glucose_trend 4\n\
+             # TYPE glucose_age_seconds gauge\n\
+             glucose_age_seconds 120\n"
+        );
+    }
+
+    #[test]
+    fn test_readings_to_csv() {
+        let readings = [
+            GlucosReading::synthetic(153, Trend::Flat, 1699110415000),
+            GlucosReading::synthetic(148, Trend::FortyFiveDown, 1699110115000),
+        ];
+
+        assert_eq!(
+            readings_to_csv(&readings),
+            "timestamp,value,trend\n\
+             2023-11-04T15:06:55.000Z,153,Flat\n\
+             2023-11-04T15:01:55.000Z,148,FortyFiveDown\n"
+        );
+        assert_eq!(readings_to_csv(&[]), "timestamp,value,trend\n");
+    }
+}
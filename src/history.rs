@@ -0,0 +1,112 @@
+use crate::client::Client;
+use crate::{
+    ClientError, Dexcom, GlucosReading, DEXCOM_READING_INTERVAL_SECS, MAX_COUNT, MAX_WINDOW_MINUTES,
+};
+
+type Result<T, C> = std::result::Result<T, ClientError<<C as Client>::Error>>;
+
+/// Pages backwards through the readings of one session, e.g. for a chart
+/// that loads older data as the user scrolls.
+///
+/// The Share API only serves windows ending at the present, so every page
+/// asks for the window from `now_ms` back past the oldest reading seen so
+/// far and keeps what is older. Paging stops at `MAX_WINDOW_MINUTES` or the
+/// start of the account's data, whichever comes first.
+pub struct HistoryCursor<'a, C: Client> {
+    dexcom: Dexcom<'a, C>,
+    session_id: String,
+    now_ms: i64,
+    oldest_ms: Option<i64>,
+}
+
+impl<'a, C: Client> HistoryCursor<'a, C> {
+    pub fn new(dexcom: Dexcom<'a, C>, session_id: &str, now_ms: i64) -> Self {
+        Self {
+            dexcom,
+            session_id: session_id.to_owned(),
+            now_ms,
+            oldest_ms: None,
+        }
+    }
+
+    pub fn dexcom(&mut self) -> &mut Dexcom<'a, C> {
+        &mut self.dexcom
+    }
+
+    /// Wall time of the oldest reading returned so far.
+    pub fn oldest_ms(&self) -> Option<i64> {
+        self.oldest_ms
+    }
+
+    /// Up to `count` readings older than any returned before, newest-first.
+    /// Empty once there is nothing older left to serve.
+    pub fn older(&mut self, count: u32) -> Result<Vec<GlucosReading>, C> {
+        let before_ms = self.oldest_ms.unwrap_or(self.now_ms + 1);
+        let span_ms = (self.now_ms - before_ms).max(0) as u64
+            + count as u64 * DEXCOM_READING_INTERVAL_SECS as u64 * 1000;
+        let minutes = span_ms.div_ceil(60_000).clamp(1, MAX_WINDOW_MINUTES as u64) as u32;
+
+        let mut readings =
+            self.dexcom
+                .get_glucose_readings(&self.session_id, minutes, MAX_COUNT)?;
+        readings.retain(|r| r.wall_time < before_ms);
+        readings.dedup_by_key(|r| r.wall_time);
+        readings.truncate(count as usize);
+
+        if let Some(oldest) = readings.last() {
+            self.oldest_ms = Some(oldest.wall_time);
+        }
+        Ok(readings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::client::{mock_client, HttpResponse};
+    use crate::testing::reading_json;
+
+    #[test]
+    fn test_pages_backwards() {
+        let body = (0..6)
+            .rev()
+            .map(|i| {
+                let time = 1699110415000i64 + i * 5 * 60_000;
+                reading_json(100 + i as i32, time)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let body = format!("[{}]", body);
+
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .times(4)
+            .returning(move |_, _, request, mut buf| {
+                let request: serde_json::Value = serde_json::from_slice(request).unwrap();
+                assert_eq!(request["maxCount"], MAX_COUNT);
+
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let values =
+            |readings: Vec<GlucosReading>| readings.iter().map(|r| r.value).collect::<Vec<_>>();
+        let mut cursor = HistoryCursor::new(
+            Dexcom::new(&mut client),
+            "a21d18db-a276-40bc-8337-77dcd02df53e",
+            1699110415000 + 25 * 60_000,
+        );
+
+        assert_eq!(values(cursor.older(2).unwrap()), [105, 104]);
+        assert_eq!(values(cursor.older(2).unwrap()), [103, 102]);
+        assert_eq!(cursor.oldest_ms(), Some(1699110415000 + 10 * 60_000));
+        assert_eq!(values(cursor.older(3).unwrap()), [101, 100]);
+        assert!(cursor.older(3).unwrap().is_empty());
+    }
+}
@@ -1,118 +1,918 @@
+pub mod alert;
+pub mod builder;
 pub mod client;
+pub mod export;
+pub mod history;
+#[cfg(feature = "nightscout")]
+pub mod nightscout;
+pub mod poll;
+#[cfg(any(feature = "log", feature = "defmt", test))]
+mod redact;
+pub mod retry;
+pub mod session;
+pub mod stats;
+#[cfg(feature = "strict")]
+mod strict;
+#[cfg(test)]
+mod testing;
+pub mod timestamp;
+pub mod transform;
+pub mod trend;
+pub mod unit;
 
-use client::Client;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use client::{Client, HttpResponse};
+use retry::RetryPolicy;
+use serde::de::{DeserializeOwned, DeserializeSeed, IgnoredAny, SeqAccess, Visitor};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+use transform::ReadingTransform;
 
-#[repr(u8)]
-#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
-pub enum Trend {
-    None,
-    DoubleUp,
-    SingleUp,
-    FortyFiveUp,
-    Flat,
-    FortyFiveDown,
-    SingleDown,
-    DoubleDown,
-    NotComputable,
-    RateOutOfRange,
-}
+pub use builder::DexcomBuilder;
+pub use session::Session;
+pub use trend::{Trend, TrendStyle};
+pub use unit::GlucoseUnit;
 
 #[repr(u8)]
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum DexcomError {
     AccountPasswordInvalid,
     AuthenticateMaxAttempsExceed,
+    /// Too many failed logins locked the account for a while.
+    AccountLocked,
     SessionNotFound,
     SessionInvalid,
     InvalidUsername,
     InvalidPassword,
+    InvalidApplicationId,
     InvalidAccountId,
+    InvalidSessionId,
     InvalidUnknown,
-    Unknown,
+    /// A 2xx account/session response that wasn't a UUID string.
+    MalformedId(String),
+    /// 503 whose body announces scheduled maintenance.
+    UnderMaintenance,
+    /// Any other 503 that didn't carry a Dexcom error payload.
+    ServerUnavailable,
+    /// A reading clamped at `SENSOR_LOW` or `SENSOR_HIGH`, rejected under
+    /// `set_reject_sentinels`.
+    SensorOutOfRange,
+    Unknown {
+        code: Option<String>,
+        message: Option<String>,
+    },
+}
+
+impl DexcomError {
+    /// The credentials (or the account id derived from them) were rejected;
+    /// the user needs to log in again rather than retry.
+    pub fn is_auth_error(&self) -> bool {
+        use DexcomError::*;
+        match self {
+            AccountPasswordInvalid
+            | AuthenticateMaxAttempsExceed
+            | AccountLocked
+            | InvalidUsername
+            | InvalidPassword
+            | InvalidApplicationId
+            | InvalidAccountId => true,
+            SessionNotFound
+            | SessionInvalid
+            | InvalidSessionId
+            | InvalidUnknown
+            | MalformedId(_)
+            | UnderMaintenance
+            | ServerUnavailable
+            | SensorOutOfRange
+            | Unknown { .. } => false,
+        }
+    }
+
+    /// The session id is missing or expired; logging in again with the same
+    /// credentials fixes it.
+    pub fn is_session_error(&self) -> bool {
+        use DexcomError::*;
+        match self {
+            SessionNotFound | SessionInvalid | InvalidSessionId => true,
+            AccountPasswordInvalid
+            | AuthenticateMaxAttempsExceed
+            | AccountLocked
+            | InvalidUsername
+            | InvalidPassword
+            | InvalidApplicationId
+            | InvalidAccountId
+            | InvalidUnknown
+            | MalformedId(_)
+            | UnderMaintenance
+            | ServerUnavailable
+            | SensorOutOfRange
+            | Unknown { .. } => false,
+        }
+    }
+}
+
+/// Dexcom Share deployment to talk to. Defaults to `Ous` when the `ous`
+/// feature is enabled and `Us` otherwise.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Region {
+    Us,
+    Ous,
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        if cfg!(feature = "ous") {
+            Region::Ous
+        } else {
+            Region::Us
+        }
+    }
+}
+
+impl Region {
+    fn other(self) -> Self {
+        match self {
+            Region::Us => Region::Ous,
+            Region::Ous => Region::Us,
+        }
+    }
+
+    fn glucose_readings_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+        }
+    }
+
+    fn data_range_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_DATA_RANGE_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_DATA_RANGE_ENDPOINT,
+        }
+    }
+
+    fn device_info_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_DEVICE_INFO_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_DEVICE_INFO_ENDPOINT,
+        }
+    }
+
+    fn login_id_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_LOGIN_ID_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_LOGIN_ID_ENDPOINT,
+        }
+    }
+
+    fn login_name_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_LOGIN_NAME_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_LOGIN_NAME_ENDPOINT,
+        }
+    }
+
+    fn authenticate_endpoint(self) -> &'static str {
+        match self {
+            Region::Us => url::us::DEXCOM_AUTHENTICATE_ENDPOINT,
+            Region::Ous => url::ous::DEXCOM_AUTHENTICATE_ENDPOINT,
+        }
+    }
+
+    pub fn endpoints(self) -> Endpoints {
+        Endpoints {
+            authenticate: self.authenticate_endpoint().into(),
+            login: self.login_id_endpoint().into(),
+            login_by_name: self.login_name_endpoint().into(),
+            glucose_readings: self.glucose_readings_endpoint().into(),
+            data_range: self.data_range_endpoint().into(),
+            device_info: self.device_info_endpoint().into(),
+        }
+    }
+}
+
+/// The Share URLs of one region, or under a custom base URL.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Endpoints {
+    pub authenticate: Cow<'static, str>,
+    pub login: Cow<'static, str>,
+    pub login_by_name: Cow<'static, str>,
+    pub glucose_readings: Cow<'static, str>,
+    pub data_range: Cow<'static, str>,
+    /// Unverified; see `Dexcom::get_device_info`.
+    pub device_info: Cow<'static, str>,
+}
+
+/// Account name, password and application id used to log in.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub account_name: String,
+    pub password: String,
+    pub application_id: String,
+}
+
+// Written by hand so the password can't end up in logs or panic messages.
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("account_name", &self.account_name)
+            .field("password", &"***")
+            .field("application_id", &self.application_id)
+            .finish()
+    }
+}
+
+// Not a `Drop` impl: that would stop callers from moving fields out of
+// `Credentials` as soon as any crate in the build enables the feature.
+/// Wipes the password in place, e.g. once the credentials are no longer
+/// needed for logins. Requires the `zeroize` feature.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Credentials {
+    fn zeroize(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+impl Credentials {
+    pub fn new(account_name: &str, password: &str, application_id: &str) -> Self {
+        Self {
+            account_name: account_name.to_owned(),
+            password: password.to_owned(),
+            application_id: application_id.to_owned(),
+        }
+    }
+}
+
+/// An unparsed response body along with its HTTP status.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// What `get_reading_state` found for the latest reading.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReadingState {
+    Available(GlucosReading),
+    /// The window held no readings, as during the two-hour sensor warmup.
+    WarmingUp,
+    /// Dexcom answered with an error about the sensor itself.
+    NoSensor,
+}
+
+/// Login round trip about to be made by `load_session_id_with_progress`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LoginStage {
+    Authenticating,
+    FetchingSession,
+}
+
+type Sleep<'a> = Box<dyn FnMut(Duration) + 'a>;
+
+type StatusPolicy<'a> = Box<dyn Fn(u16) -> bool + 'a>;
+
+type Preflight<'a> = Box<dyn FnMut() -> std::result::Result<Vec<(String, String)>, String> + 'a>;
+
+// A single-token bucket: one API call per `interval`, measured with the
+// caller's clock since the crate has none of its own. Within an operation
+// only the first request waits; its follow-ups ride on the same token.
+struct RateLimit<'a> {
+    interval: Duration,
+    now_ms: Box<dyn Fn() -> i64 + 'a>,
+    sleep: Option<Sleep<'a>>,
+    last_request_ms: Option<i64>,
+    in_operation: bool,
+    paid: bool,
+}
+
+pub const DEFAULT_USER_AGENT: &str = concat!("rsdexcom/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_APPLICATION_ID: &str = "d89443d2-327c-4a6f-89e5-496bbb0317db";
+pub const DEFAULT_BUFFER_SIZE: usize = 512;
+pub const DEFAULT_CONTENT_TYPE: &str = "application/json";
+pub const DEFAULT_MAX_REQUEST_SIZE: usize = 1024;
+pub const MAX_WINDOW_MINUTES: u32 = 1440;
+pub const MAX_COUNT: u32 = 288;
+/// How often the sensor produces a reading.
+pub const DEXCOM_READING_INTERVAL_SECS: u32 = 300;
+pub const PLAUSIBLE_VALUES: std::ops::RangeInclusive<i32> = 1..=600;
+
+// Limits of the sensor's range. Share clamps readings past them to the limit
+// itself, so a value at or beyond either one only bounds the real level; see
+// `GlucosReading::is_below_range` and `is_above_range`.
+pub const SENSOR_LOW: i32 = 40;
+pub const SENSOR_HIGH: i32 = 400;
+
+// Upper bound on the JSON size of a single reading, used to size the response
+// buffer for multi-reading requests.
+const MAX_READING_JSON_LEN: usize = 160;
+
+// Either the caller's client or one the `Dexcom` owns, so an owned
+// `Dexcom<'static, _>` can be stored or moved freely.
+enum ClientSlot<'a, C> {
+    Borrowed(&'a mut C),
+    Owned(C),
+}
+
+impl<C> std::ops::Deref for ClientSlot<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        match self {
+            ClientSlot::Borrowed(client) => client,
+            ClientSlot::Owned(client) => client,
+        }
+    }
+}
+
+impl<C> std::ops::DerefMut for ClientSlot<'_, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        match self {
+            ClientSlot::Borrowed(client) => client,
+            ClientSlot::Owned(client) => client,
+        }
+    }
 }
 
 pub struct Dexcom<'a, C: Client> {
-    client: &'a mut C,
+    client: ClientSlot<'a, C>,
+    user_agent: String,
+    application_id: String,
+    buffer_size: usize,
+    region: Region,
+    auto_region: bool,
+    request_id: Option<String>,
+    auth_retry: Option<(RetryPolicy, Sleep<'a>)>,
+    rate_limit: Option<RateLimit<'a>>,
+    value_validation: bool,
+    reject_sentinels: bool,
+    lenient_readings: bool,
+    skipped_readings: usize,
+    request_buf: Vec<u8>,
+    max_connection_retries: u32,
+    default_window: (u32, u32),
+    base_url: Option<String>,
+    content_type: String,
+    headers: Vec<(String, String)>,
+    preflight: Option<Preflight<'a>>,
+    preflight_headers: Vec<(String, String)>,
+    success_status: Option<StatusPolicy<'a>>,
+    transforms: Vec<Box<dyn ReadingTransform + 'a>>,
+    response_buf: Vec<u8>,
+    max_request_size: usize,
 }
 
+// Request bodies serialize their fields in declaration order, which is part
+// of the wire contract (see `test_request_field_order`); keep it when editing.
+// String fields are generic so requests can be built from borrowed or owned
+// strings alike.
 #[derive(Serialize)]
-struct GetLatestGlucoseValuesRequest<'a> {
+struct GetLatestGlucoseValuesRequest<S> {
     #[serde(rename = "sessionId")]
-    session_id: &'a str,
+    session_id: S,
     minutes: u32,
     #[serde(rename = "maxCount")]
     max_count: u32,
+    #[serde(rename = "applicationId")]
+    application_id: S,
 }
 
 #[derive(Serialize)]
-struct GetAccountIdRequest<'a> {
+struct GetAccountIdRequest<S> {
     #[serde(rename = "accountName")]
-    account_name: &'a str,
-    password: &'a str,
+    account_name: S,
+    password: S,
     #[serde(rename = "applicationId")]
-    application_id: &'a str,
+    application_id: S,
 }
 
 #[derive(Serialize)]
-struct GetSessionIdRequest<'a> {
+struct GetSessionIdRequest<S> {
     #[serde(rename = "accountId")]
-    account_id: &'a str,
-    password: &'a str,
+    account_id: S,
+    password: S,
     #[serde(rename = "applicationId")]
-    application_id: &'a str,
+    application_id: S,
+}
+
+impl<S: fmt::Debug> fmt::Debug for GetAccountIdRequest<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GetAccountIdRequest")
+            .field("account_name", &self.account_name)
+            .field("password", &"***")
+            .field("application_id", &self.application_id)
+            .finish()
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for GetSessionIdRequest<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GetSessionIdRequest")
+            .field("account_id", &self.account_id)
+            .field("password", &"***")
+            .field("application_id", &self.application_id)
+            .finish()
+    }
+}
+
+// Body of the endpoints that only take the session id.
+#[derive(Serialize)]
+struct SessionIdRequest<'a> {
+    #[serde(rename = "sessionId")]
+    session_id: &'a str,
 }
 
-#[cfg_attr(test, derive(PartialEq))]
-#[derive(Deserialize, Debug, Clone, Copy)]
+/// Transmitter and sensor details of the account's current device.
+///
+/// Experimental: the field names are unverified against the live service;
+/// see `Dexcom::get_device_info`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeviceInfo {
+    pub transmitter_id: String,
+    /// When the current sensor session started, in epoch ms.
+    #[serde(with = "timestamp")]
+    pub sensor_start_time: i64,
+    pub device_model: String,
+}
+
+/// Earliest and latest glucose timestamps (epoch ms) available for an account.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub struct DataRange {
+    #[serde(with = "timestamp")]
+    pub start: i64,
+    #[serde(with = "timestamp")]
+    pub end: i64,
+}
+
+// Wire names are PascalCase except for the three `Date(...)` timestamps; the
+// lowercase aliases tolerate feeds that re-case the fields.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(rename_all = "PascalCase")]
 pub struct GlucosReading {
-    #[serde(rename = "Value")]
+    #[serde(alias = "value", deserialize_with = "deserialize_value")]
     pub value: i32,
-    #[serde(rename = "Trend")]
+    #[serde(alias = "trend")]
     pub trend: Trend,
+    #[serde(rename = "WT", alias = "wt", with = "timestamp")]
+    pub wall_time: i64,
+    #[serde(rename = "ST", alias = "st", with = "timestamp")]
+    pub system_time: i64,
+    #[serde(rename = "DT", alias = "dt", with = "timestamp")]
+    pub display_time: i64,
+    #[serde(default, skip_serializing_if = "Source::is_dexcom_share")]
+    pub source: Source,
+}
+
+/// Order of the readings returned by `get_glucose_readings_ordered`, by wall
+/// time.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum ReadingOrder {
+    /// Oldest first.
+    Ascending,
+    /// Newest first, as `get_glucose_readings` returns them.
+    #[default]
+    Descending,
+}
+
+/// How old a reading is, by whole `minutes_ago`: `Fresh` up to 5 minutes,
+/// `Recent` up to 10, `Stale` up to 20 and `Old` beyond.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum Staleness {
+    Fresh,
+    Recent,
+    Stale,
+    Old,
+}
+
+/// Whether the transmitter had contact with the sensor, going by the trend
+/// Dexcom reported alongside the reading.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SignalStatus {
+    Ok,
+    /// `Trend::NotComputable`: too few recent readings to compute a trend.
+    NotComputable,
+    /// `Trend::RateOutOfRange`: the rate of change is implausible.
+    OutOfRange,
+}
+
+/// Where a reading came from, so non-authoritative readings can be told apart.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Source {
+    #[default]
+    DexcomShare,
+    /// A Share reading with a locally computed field, e.g. a trend filled in
+    /// by `stats::fill_trends` or `transform::FillTrend`.
+    Computed,
+    /// Made up by the caller, see `GlucosReading::synthetic`.
+    Synthetic,
+}
+
+impl Source {
+    fn is_dexcom_share(&self) -> bool {
+        *self == Source::DexcomShare
+    }
+
+    // A synthetic reading stays synthetic when a field is computed for it.
+    pub(crate) fn computed(self) -> Source {
+        match self {
+            Source::DexcomShare => Source::Computed,
+            source => source,
+        }
+    }
+}
+
+impl GlucosReading {
+    /// A reading with all three timestamps at the epoch, e.g. for tests of
+    /// code that consumes readings.
+    ///
+    /// ```
+    /// use dexcom::{GlucosReading, Trend};
+    ///
+    /// let reading = GlucosReading::new(153, Trend::Flat);
+    /// assert_eq!(reading.value, 153);
+    ///
+    /// let later = GlucosReading::with_wall_time(160, Trend::SingleUp, 1699110415000);
+    /// assert_eq!(later.display_time, 1699110415000);
+    /// ```
+    pub fn new(value: i32, trend: Trend) -> Self {
+        Self::with_wall_time(value, trend, 0)
+    }
+
+    /// `new` with all three timestamps set to `wall_time`.
+    pub fn with_wall_time(value: i32, trend: Trend, wall_time: i64) -> Self {
+        Self {
+            source: Source::DexcomShare,
+            ..Self::synthetic(value, trend, wall_time)
+        }
+    }
+
+    /// A made-up reading at `wall_time`, tagged `Source::Synthetic`.
+    pub fn synthetic(value: i32, trend: Trend, wall_time: i64) -> Self {
+        Self {
+            value,
+            trend,
+            wall_time,
+            system_time: wall_time,
+            display_time: wall_time,
+            source: Source::Synthetic,
+        }
+    }
+
+    /// Packs the reading into a fixed 7-byte little-endian record: value
+    /// (`u16`), trend code (`u8`) and wall time in epoch seconds (`u32`).
+    /// Milliseconds, the system and display times and `source` are dropped;
+    /// out-of-range values and times are clamped.
+    pub fn to_bytes(&self) -> [u8; 7] {
+        let value = self.value.clamp(0, u16::MAX as i32) as u16;
+        let secs = self.wall_time.div_euclid(1000).clamp(0, u32::MAX as i64) as u32;
+
+        let mut bytes = [0; 7];
+        bytes[..2].copy_from_slice(&value.to_le_bytes());
+        bytes[2] = self.trend as u8;
+        bytes[3..].copy_from_slice(&secs.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a `to_bytes` record, `None` when the trend byte isn't a valid
+    /// `Trend::index`. All three timestamps are set to the stored wall time.
+    pub fn from_bytes(bytes: &[u8; 7]) -> Option<Self> {
+        let trend = *Trend::all().get(bytes[2] as usize)?;
+        let value = u16::from_le_bytes([bytes[0], bytes[1]]) as i32;
+        let secs = u32::from_le_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]);
+        let wall_time = secs as i64 * 1000;
+
+        Some(Self {
+            value,
+            trend,
+            wall_time,
+            system_time: wall_time,
+            display_time: wall_time,
+            source: Source::DexcomShare,
+        })
+    }
+
+    /// Whole minutes elapsed between the reading's wall time and `now_epoch_ms`,
+    /// clamped to 0 when the local clock is behind the reading.
+    pub fn minutes_ago(&self, now_epoch_ms: i64) -> i64 {
+        (now_epoch_ms - self.wall_time).max(0) / 60_000
+    }
+
+    pub fn staleness(&self, now_epoch_ms: i64) -> Staleness {
+        match self.minutes_ago(now_epoch_ms) {
+            0..=5 => Staleness::Fresh,
+            6..=10 => Staleness::Recent,
+            11..=20 => Staleness::Stale,
+            _ => Staleness::Old,
+        }
+    }
+
+    pub fn signal_status(&self) -> SignalStatus {
+        match self.trend {
+            Trend::NotComputable => SignalStatus::NotComputable,
+            Trend::RateOutOfRange => SignalStatus::OutOfRange,
+            _ => SignalStatus::Ok,
+        }
+    }
+
+    /// Minutes until `threshold` is reached at the trend's rate, as an
+    /// `(optimistic, pessimistic)` range from a ±20% band on that rate. `None`
+    /// when the reading isn't heading toward the threshold.
+    pub fn time_to_threshold_with_uncertainty(&self, threshold: i32) -> Option<(u32, u32)> {
+        let rate = self.trend.rate_per_minute()?;
+        let distance = (threshold - self.value) as f32;
+
+        if distance == 0.0 {
+            return Some((0, 0));
+        }
+        if rate == 0.0 || distance.signum() != rate.signum() {
+            return None;
+        }
+
+        let minutes = |rate: f32| (distance / rate).round() as u32;
+        Some((minutes(rate * 0.8), minutes(rate * 1.2)))
+    }
+
+    /// Cheap FNV-1a hash of value, trend and wall time for change detection
+    /// between polls. Collisions are possible in principle but extremely
+    /// unlikely between consecutive realistic readings.
+    pub fn state_hash(&self) -> u32 {
+        const OFFSET_BASIS: u32 = 0x811c_9dc5;
+        const PRIME: u32 = 0x0100_0193;
+
+        self.value
+            .to_le_bytes()
+            .into_iter()
+            .chain([self.trend as u8])
+            .chain(self.wall_time.to_le_bytes())
+            .fold(OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u32).wrapping_mul(PRIME)
+            })
+    }
+
+    /// True when both readings have the same trend and values at most
+    /// `tolerance` mg/dL apart. Timestamps and source are ignored.
+    pub fn approx_eq(&self, other: &GlucosReading, tolerance: i32) -> bool {
+        self.trend == other.trend && (self.value - other.value).abs() <= tolerance
+    }
+
+    /// Whether the sensor read LOW, i.e. the value is clamped at or below
+    /// `SENSOR_LOW`.
+    pub fn is_below_range(&self) -> bool {
+        self.value <= SENSOR_LOW
+    }
+
+    /// Whether the sensor read HIGH, i.e. the value is clamped at or above
+    /// `SENSOR_HIGH`.
+    pub fn is_above_range(&self) -> bool {
+        self.value >= SENSOR_HIGH
+    }
+
+    pub fn value_in(&self, unit: GlucoseUnit) -> f32 {
+        unit.from_mg_dl(self.value)
+    }
+
+    /// mmol/L times ten, rounded to the nearest integer, using only integer
+    /// arithmetic for targets without an FPU. The factor is
+    /// `GlucoseUnit::MMOL_L_PER_MG_DL`, as for `value_in`.
+    pub fn mmol_l_tenths(&self) -> i32 {
+        (self.value * 555 + 500).div_euclid(1000)
+    }
+
+    /// Like `Display`, but in `unit` and with the unit symbol,
+    /// e.g. `8.5 mmol/L →`.
+    pub fn summary(&self, unit: GlucoseUnit) -> String {
+        let value = if self.is_below_range() {
+            "LOW".to_owned()
+        } else if self.is_above_range() {
+            "HIGH".to_owned()
+        } else {
+            match unit {
+                GlucoseUnit::MgDl => format!("{} {}", self.value, unit),
+                GlucoseUnit::MmolL => {
+                    let tenths = self.mmol_l_tenths();
+                    format!("{}.{} {}", tenths / 10, tenths % 10, unit)
+                }
+            }
+        };
+
+        match self.trend.arrow() {
+            "" => value,
+            arrow => format!("{} {}", value, arrow),
+        }
+    }
+}
+
+impl fmt::Display for GlucosReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_below_range() {
+            f.write_str("LOW")?;
+        } else if self.is_above_range() {
+            f.write_str("HIGH")?;
+        } else {
+            write!(f, "{}", self.value)?;
+        }
+
+        match self.trend.arrow() {
+            "" => Ok(()),
+            arrow => write!(f, " {}", arrow),
+        }
+    }
+}
+
+// Some proxied feeds send `Value` as a float, and some mirrors as a numeric
+// string; either is rounded to the nearest mg/dL.
+fn deserialize_value<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<i32, D::Error> {
+    let value = deserializer.deserialize_any(ValueVisitor)?.round();
+    if !value.is_finite() || value < i32::MIN as f64 || value > i32::MAX as f64 {
+        return Err(de::Error::custom("glucose value out of range"));
+    }
+    Ok(value as i32)
+}
+
+struct ValueVisitor;
+
+impl Visitor<'_> for ValueVisitor {
+    type Value = f64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a glucose value as a number or numeric string")
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<f64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<f64, E> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| E::custom(format_args!("invalid glucose value {:?}", value)))
+    }
 }
 
 #[derive(Deserialize)]
 struct DexcomErrorResponse<'a> {
     #[serde(rename = "Code")]
     code: Option<&'a str>,
-    message: Option<&'a str>,
+    #[serde(rename = "Message", alias = "message", borrow)]
+    message: Option<Cow<'a, str>>,
+    #[serde(rename = "SubCode", borrow)]
+    sub_code: Option<Cow<'a, str>>,
+    #[serde(rename = "TypeName")]
+    type_name: Option<&'a str>,
 }
 
 impl From<DexcomErrorResponse<'_>> for DexcomError {
     fn from(val: DexcomErrorResponse<'_>) -> Self {
         use DexcomError::*;
         match val.code {
-            None => Unknown,
+            None => unknown(&val),
             Some(code) => match code {
                 "SessionIdNotFound" => SessionNotFound,
                 "SessionNotValid" => SessionInvalid,
                 "AccountPasswordInvalid" => AccountPasswordInvalid,
                 "SSO_AuthenticateMaxAttemptsExceeed" => AuthenticateMaxAttempsExceed,
-                "InvalidArgument" => {
-                    match val.message {
-                        None => InvalidUnknown,
-                        Some(message) => {
-                            if message.contains("accountName") {
-                                InvalidUsername
-                            } else if message.contains("password") {
-                                InvalidPassword
-                            } else if message.contains("UUID") {
-                                InvalidAccountId
-                            } else {
-                                InvalidUnknown
-                            }
-                        }
-                    }
-                },
-                _ => Unknown,
+                "SSO_AuthenticateAccountLocked" | "AccountLocked" => AccountLocked,
+                "InvalidArgument" => invalid_argument(&val),
+                _ => unknown(&val),
             },
         }
     }
 }
 
+// Some endpoints wrap the error object in an array; only the first entry is
+// used.
+#[cfg(not(feature = "lightweight-errors"))]
+fn error_response_from_slice(buf: &[u8]) -> Option<DexcomErrorResponse<'_>> {
+    from_slice::<DexcomErrorResponse>(buf).ok().or_else(|| {
+        from_slice::<Vec<DexcomErrorResponse>>(buf)
+            .ok()
+            .and_then(|responses| responses.into_iter().next())
+    })
+}
+
+#[cfg(feature = "lightweight-errors")]
+fn error_response_from_slice(buf: &[u8]) -> Option<DexcomErrorResponse<'_>> {
+    scan_error_response(buf)
+}
+
+// Finds the fields by scanning for `"Name":"..."` instead of parsing JSON, to
+// keep serde_json out of the error path. Values come back raw, with any
+// escapes left in, which is enough to match codes and parameter names. A
+// body without a `Code` isn't treated as a Dexcom error.
+#[cfg(any(feature = "lightweight-errors", test))]
+fn scan_error_response(buf: &[u8]) -> Option<DexcomErrorResponse<'_>> {
+    Some(DexcomErrorResponse {
+        code: Some(scan_string_field(buf, "Code")?),
+        message: scan_string_field(buf, "Message")
+            .or_else(|| scan_string_field(buf, "message"))
+            .map(Cow::Borrowed),
+        sub_code: scan_string_field(buf, "SubCode").map(Cow::Borrowed),
+        type_name: scan_string_field(buf, "TypeName"),
+    })
+}
+
+#[cfg(any(feature = "lightweight-errors", test))]
+fn scan_string_field<'a>(buf: &'a [u8], name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\"", name);
+    let start = buf
+        .windows(key.len())
+        .position(|window| window == key.as_bytes())?
+        + key.len();
+
+    let rest = buf[start..].trim_ascii_start().strip_prefix(b":")?;
+    let value = rest.trim_ascii_start().strip_prefix(b"\"")?;
+
+    let mut escaped = false;
+    let end = value.iter().position(|&byte| {
+        let closing = byte == b'"' && !escaped;
+        escaped = byte == b'\\' && !escaped;
+        closing
+    })?;
+    std::str::from_utf8(&value[..end]).ok()
+}
+
+fn unknown(val: &DexcomErrorResponse<'_>) -> DexcomError {
+    DexcomError::Unknown {
+        code: val.code.map(str::to_owned),
+        message: val.message.as_deref().map(str::to_owned),
+    }
+}
+
+// `InvalidArgument` payloads carry the .NET exception type in `TypeName` and an
+// `<OnlineException ... />` dump in `SubCode`, both of which end their message
+// with `Parameter name: <name>`. Guid parse failures come back as a
+// `FormatException` instead.
+fn invalid_argument(val: &DexcomErrorResponse<'_>) -> DexcomError {
+    use DexcomError::*;
+
+    if val.type_name == Some("FormatException") {
+        return InvalidAccountId;
+    }
+
+    let parameter = [&val.sub_code, &val.message]
+        .into_iter()
+        .flatten()
+        .find_map(|text| parameter_name(text));
+
+    match parameter {
+        Some("accountName") => InvalidUsername,
+        Some("password") => InvalidPassword,
+        Some("applicationId") => InvalidApplicationId,
+        Some("accountId") => InvalidAccountId,
+        Some("sessionId") => InvalidSessionId,
+        _ => match val.message.as_deref() {
+            Some(message) if message.contains("accountName") => InvalidUsername,
+            Some(message) if message.contains("password") => InvalidPassword,
+            Some(message) if message.contains("UUID") => InvalidAccountId,
+            _ => InvalidUnknown,
+        },
+    }
+}
+
+fn parameter_name(text: &str) -> Option<&str> {
+    const MARKER: &str = "Parameter name: ";
+
+    let start = text.find(MARKER)? + MARKER.len();
+    let name = &text[start..];
+    let end = name
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(name.len());
+
+    Some(&name[..end]).filter(|name| !name.is_empty())
+}
+
 #[derive(Debug)]
-pub struct SerdeJsonError(pub serde_json::Error);
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SerdeJsonError(
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))] pub serde_json::Error,
+);
+
+/// Compares the error category and position, not the message: two errors
+/// from the same kind of failure at the same line and column are equal.
+impl PartialEq for SerdeJsonError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.classify() == other.0.classify()
+            && self.0.line() == other.0.line()
+            && self.0.column() == other.0.column()
+    }
+}
 
 impl From<serde_json::Error> for SerdeJsonError {
     fn from(value: serde_json::Error) -> Self {
@@ -120,11 +920,160 @@ impl From<serde_json::Error> for SerdeJsonError {
     }
 }
 
-#[derive(Debug)]
+// The defmt output of a connection error goes through its `Debug` impl, so
+// client error types needn't implement `defmt::Format` themselves.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum ClientError<E: embedded_svc::io::Error> {
-    ConnectionError(E),
-    DexcomError(DexcomError),
+    ConnectionError(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] E),
+    /// A decoded Dexcom error along with the HTTP status that carried it, or
+    /// `None` when the error was caught before sending anything.
+    DexcomError(DexcomError, Option<u16>),
     JSONError(SerdeJsonError),
+    /// A reading outside `PLAUSIBLE_VALUES`, with value validation enabled.
+    ImplausibleReading(i32),
+    /// The response body is gzip-compressed and the `gzip` feature is off (or
+    /// it failed to decompress). Disable `Accept-Encoding` in the client.
+    CompressedResponse,
+    /// A non-2xx response whose body isn't a Dexcom error payload, e.g. an
+    /// HTML error page from a proxy.
+    HttpStatus(u16),
+    /// The serialized request would exceed `set_max_request_size`, given
+    /// here; nothing was sent.
+    RequestTooLarge(usize),
+    /// Issued within `set_min_request_interval` of the previous request;
+    /// retry after the given wait. Nothing was sent.
+    RateLimited(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] Duration),
+    /// The `set_preflight` hook failed with the given message; no login
+    /// request was sent.
+    Preflight(String),
+    /// A 3xx response redirecting to the given URL, e.g. another regional
+    /// host. Retry the request there.
+    Redirect(String),
+}
+
+/// Coarse state for UI layers, collapsed from a `ClientError`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConnectionStatus {
+    Offline,
+    AuthFailed,
+    SessionExpired,
+    ServiceUnavailable,
+    ProtocolError,
+    /// Held back by `set_min_request_interval`; Dexcom wasn't contacted.
+    Throttled,
+}
+
+impl<E: embedded_svc::io::Error> ClientError<E> {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::DexcomError(_, status) => *status,
+            ClientError::HttpStatus(status) => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Collapses the error into a coarse `ConnectionStatus`.
+    pub fn connection_status(&self) -> ConnectionStatus {
+        match self {
+            ClientError::ConnectionError(_) => ConnectionStatus::Offline,
+            ClientError::DexcomError(error, _) if error.is_auth_error() => {
+                ConnectionStatus::AuthFailed
+            }
+            ClientError::DexcomError(error, _) if error.is_session_error() => {
+                ConnectionStatus::SessionExpired
+            }
+            ClientError::DexcomError(
+                DexcomError::UnderMaintenance | DexcomError::ServerUnavailable,
+                _,
+            ) => ConnectionStatus::ServiceUnavailable,
+            ClientError::HttpStatus(500..=599) => ConnectionStatus::ServiceUnavailable,
+            ClientError::RateLimited(_) => ConnectionStatus::Throttled,
+            ClientError::DexcomError(..)
+            | ClientError::HttpStatus(_)
+            | ClientError::JSONError(_)
+            | ClientError::ImplausibleReading(_)
+            | ClientError::CompressedResponse
+            | ClientError::RequestTooLarge(_)
+            | ClientError::Redirect(_) => ConnectionStatus::ProtocolError,
+            // The hook's documented job is proxy authentication.
+            ClientError::Preflight(_) => ConnectionStatus::AuthFailed,
+        }
+    }
+}
+
+impl fmt::Display for DexcomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use DexcomError::*;
+        match self {
+            AccountPasswordInvalid => f.write_str("account name or password is invalid"),
+            AuthenticateMaxAttempsExceed => f.write_str("too many login attempts"),
+            AccountLocked => f.write_str("account is locked"),
+            SessionNotFound => f.write_str("session not found"),
+            SessionInvalid => f.write_str("session is no longer valid"),
+            InvalidUsername => f.write_str("invalid account name"),
+            InvalidPassword => f.write_str("invalid password"),
+            InvalidApplicationId => f.write_str("invalid application id"),
+            InvalidAccountId => f.write_str("invalid account id"),
+            InvalidSessionId => f.write_str("invalid session id"),
+            InvalidUnknown => f.write_str("invalid argument"),
+            MalformedId(id) => write!(f, "malformed id {:?}", id),
+            UnderMaintenance => f.write_str("Dexcom Share is under maintenance"),
+            ServerUnavailable => f.write_str("Dexcom Share is unavailable"),
+            SensorOutOfRange => f.write_str("reading is at the sensor range limit"),
+            Unknown { code, message } => write!(
+                f,
+                "unknown Dexcom error {}: {}",
+                code.as_deref().unwrap_or("without code"),
+                message.as_deref().unwrap_or("no message")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DexcomError {}
+
+impl<E: embedded_svc::io::Error> fmt::Display for ClientError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // `embedded_svc::io::Error` only guarantees `Debug`.
+            ClientError::ConnectionError(error) => write!(f, "connection error: {:?}", error),
+            ClientError::DexcomError(error, None) => write!(f, "{}", error),
+            ClientError::DexcomError(error, Some(status)) => {
+                write!(f, "{} (HTTP {})", error, status)
+            }
+            ClientError::JSONError(error) => write!(f, "invalid JSON response: {}", error.0),
+            ClientError::ImplausibleReading(value) => write!(f, "implausible reading {}", value),
+            ClientError::CompressedResponse => f.write_str("response body is compressed"),
+            ClientError::HttpStatus(status) => write!(f, "unexpected HTTP status {}", status),
+            ClientError::RequestTooLarge(limit) => {
+                write!(f, "request body exceeds {} bytes", limit)
+            }
+            ClientError::RateLimited(wait) => write!(f, "rate limited, retry in {:?}", wait),
+            ClientError::Redirect(location) => write!(f, "redirected to {}", location),
+            ClientError::Preflight(message) => write!(f, "preflight failed: {}", message),
+        }
+    }
+}
+
+impl<E: embedded_svc::io::Error> std::error::Error for ClientError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::DexcomError(error, _) => Some(error),
+            ClientError::JSONError(error) => Some(&error.0),
+            _ => None,
+        }
+    }
+}
+
+impl<E: embedded_svc::io::Error + Send + Sync + 'static> ClientError<E> {
+    /// Boxes the error for `?` into `anyhow` or `Box<dyn Error>` based error
+    /// types. All variants are `Send + Sync` as long as the client's error
+    /// type is.
+    pub fn into_boxed(self) -> Box<dyn std::error::Error + Send + Sync> {
+        Box::new(self)
+    }
 }
 
 impl<E: embedded_svc::io::Error> From<SerdeJsonError> for ClientError<E> {
@@ -139,47 +1088,789 @@ impl<E: embedded_svc::io::Error> From<E> for ClientError<E> {
     }
 }
 
-type Result<T, C> = std::result::Result<T, ClientError<<C as Client>::Error>>;
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
 
-impl<'a, C: Client> Dexcom<'a, C> {
-    pub fn new(client: &'a mut C) -> Self {
-        Self { client }
+// Some HTTP clients send `Accept-Encoding: gzip` on their own, so a gzip
+// body is inflated when the `gzip` feature is on and rejected otherwise.
+fn decode_body(buf: &[u8]) -> Option<Cow<'_, [u8]>> {
+    if !buf.starts_with(GZIP_MAGIC) {
+        return Some(Cow::Borrowed(buf));
     }
 
-    fn post_request<S: Serialize, D: DeserializeOwned>(
-        &mut self,
-        uri: &str,
-        request: &S,
-    ) -> Result<D, C> {
-        let body = serde_json::to_vec(&request).map_err(SerdeJsonError)?;
-        let mut buf = [0; 512];
-
-        let (size, status_code) = self.client.post_request(
-            uri,
-            &[
-                ("Content-Type", "application/json"),
-                ("User-Agent", "rsdexcom/0.0.1"),
-            ],
-            &body,
-            &mut buf,
-        )?;
+    #[cfg(feature = "gzip")]
+    {
+        use std::io::Read;
 
-        let buf = &buf[..size];
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(buf)
+            .read_to_end(&mut decoded)
+            .ok()
+            .map(|_| Cow::Owned(decoded))
+    }
 
-        #[cfg(feature = "log")]
-        log::info!("{:?}", String::from_utf8(buf.to_vec()));
+    #[cfg(not(feature = "gzip"))]
+    None
+}
+
+// Fails the write that would take `buf` past `limit` and records that it
+// did, so the error can be told apart from a serialization failure.
+struct BoundedWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl std::io::Write for BoundedWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            self.exceeded = true;
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+
+        // A plain reallocation would leave a copy of the body behind in
+        // freed memory, so the old allocation is wiped after moving.
+        #[cfg(feature = "zeroize")]
+        if self.buf.len() + data.len() > self.buf.capacity() {
+            let needed = self.buf.len() + data.len();
+            let mut grown = Vec::with_capacity(needed.max(self.buf.capacity() * 2).min(self.limit));
+            grown.extend_from_slice(self.buf);
+            zeroize::Zeroize::zeroize(self.buf);
+            *self.buf = grown;
+        }
+
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+
+// Some proxies prepend a BOM or pad the JSON with whitespace, and serde_json
+// rejects a leading BOM.
+fn trim_body(buf: &[u8]) -> &[u8] {
+    let buf = buf.trim_ascii();
+    buf.strip_prefix(UTF8_BOM).map_or(buf, <[u8]>::trim_ascii)
+}
+
+// The maintenance page may be HTML or a JSON error; both mention the word.
+fn is_maintenance_body(buf: &[u8]) -> bool {
+    const NEEDLE: &[u8] = b"maintenance";
+    buf.windows(NEEDLE.len())
+        .any(|window| window.eq_ignore_ascii_case(NEEDLE))
+}
+
+const NIL_UUID: &str = "00000000-0000-0000-0000-000000000000";
+
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(not(feature = "strict"))]
+use serde_json::from_slice;
+#[cfg(feature = "strict")]
+use strict::{from_slice, from_slice_seed};
+
+#[cfg(not(feature = "strict"))]
+fn from_slice_seed<'de, T: DeserializeSeed<'de>>(
+    buf: &'de [u8],
+    seed: T,
+) -> serde_json::Result<T::Value> {
+    let mut deserializer = serde_json::Deserializer::from_slice(buf);
+    let value = seed.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+// Deserializes a JSON array of readings into a caller-provided slice. Once
+// it is full, each further reading replaces the oldest one if it is newer,
+// so the slice ends up with the newest readings. The value is the count
+// written.
+struct ReadingsInto<'a>(&'a mut [GlucosReading]);
+
+impl<'de> DeserializeSeed<'de> for ReadingsInto<'_> {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ReadingsInto<'_> {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of glucose readings")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<usize, A::Error> {
+        let mut count = 0;
+        while count < self.0.len() {
+            match seq.next_element()? {
+                Some(reading) => self.0[count] = reading,
+                None => return Ok(count),
+            }
+            count += 1;
+        }
+
+        if self.0.is_empty() {
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            return Ok(0);
+        }
+
+        while let Some(reading) = seq.next_element::<GlucosReading>()? {
+            let oldest = self
+                .0
+                .iter_mut()
+                .min_by_key(|r| r.wall_time)
+                .expect("the slice is not empty");
+            if reading.wall_time > oldest.wall_time {
+                *oldest = reading;
+            }
+        }
+        Ok(count)
+    }
+}
+
+// Some proxies unwrap the one-element array into a bare reading object; both
+// forms decode to a list of readings.
+struct Readings(Vec<GlucosReading>);
+
+impl<'de> Deserialize<'de> for Readings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ReadingsVisitor)
+    }
+}
+
+struct ReadingsVisitor;
+
+impl<'de> Visitor<'de> for ReadingsVisitor {
+    type Value = Readings;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a glucose reading or an array of them")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> std::result::Result<Readings, A::Error> {
+        Vec::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Readings)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> std::result::Result<Readings, A::Error> {
+        GlucosReading::deserialize(de::value::MapAccessDeserializer::new(map))
+            .map(|reading| Readings(vec![reading]))
+    }
+}
+
+// `Readings` decoding each array element on its own and counting the ones
+// that fail instead of failing the whole response.
+struct LenientReadings {
+    readings: Vec<GlucosReading>,
+    skipped: usize,
+}
+
+impl<'de> Deserialize<'de> for LenientReadings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(LenientReadingsVisitor)
+    }
+}
+
+struct LenientReadingsVisitor;
+
+impl<'de> Visitor<'de> for LenientReadingsVisitor {
+    type Value = LenientReadings;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a glucose reading or an array of them")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<LenientReadings, A::Error> {
+        let mut readings = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        let mut skipped = 0;
+
+        while let Some(element) = seq.next_element::<serde_json::Value>()? {
+            match GlucosReading::deserialize(&element) {
+                Ok(reading) => readings.push(reading),
+                Err(_error) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("skipping malformed reading: {}", _error);
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!(
+                        "skipping malformed reading: {=str}",
+                        _error.to_string().as_str()
+                    );
+                    skipped += 1;
+                }
+            }
+        }
+
+        Ok(LenientReadings { readings, skipped })
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(
+        self,
+        map: A,
+    ) -> std::result::Result<LenientReadings, A::Error> {
+        let Readings(readings) = ReadingsVisitor.visit_map(map)?;
+        Ok(LenientReadings {
+            readings,
+            skipped: 0,
+        })
+    }
+}
+
+// The Share API has no dedicated code for a missing or expired sensor, so
+// this looks for the word in an otherwise unrecognized error.
+fn is_sensor_error(error: &DexcomError) -> bool {
+    match error {
+        DexcomError::Unknown { code, message } => [code, message]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_ascii_lowercase().contains("sensor")),
+        _ => false,
+    }
+}
+
+fn is_region_mismatch(error: &DexcomError) -> bool {
+    matches!(
+        error,
+        DexcomError::AccountPasswordInvalid
+            | DexcomError::SessionNotFound
+            | DexcomError::SessionInvalid
+    )
+}
+
+type Result<T, C> = std::result::Result<T, ClientError<<C as Client>::Error>>;
+
+impl<'a, C: Client> Dexcom<'a, C> {
+    pub fn new(client: &'a mut C) -> Self {
+        Self::with_slot(ClientSlot::Borrowed(client))
+    }
+
+    /// Takes ownership of `client` instead of borrowing it. Pass an
+    /// `Rc<RefCell<_>>` or `Arc<Mutex<_>>` to share one client between
+    /// several `Dexcom`s.
+    pub fn with_client(client: C) -> Self {
+        Self::with_slot(ClientSlot::Owned(client))
+    }
+
+    fn with_slot(client: ClientSlot<'a, C>) -> Self {
+        Self {
+            client,
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+            application_id: DEFAULT_APPLICATION_ID.to_owned(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            region: Region::default(),
+            auto_region: false,
+            request_id: None,
+            auth_retry: None,
+            rate_limit: None,
+            value_validation: false,
+            reject_sentinels: false,
+            lenient_readings: false,
+            skipped_readings: 0,
+            request_buf: Vec::new(),
+            max_connection_retries: 0,
+            default_window: (MAX_WINDOW_MINUTES, 1),
+            base_url: None,
+            content_type: DEFAULT_CONTENT_TYPE.to_owned(),
+            headers: Vec::new(),
+            preflight: None,
+            preflight_headers: Vec::new(),
+            success_status: None,
+            transforms: Vec::new(),
+            response_buf: Vec::new(),
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+        }
+    }
+
+    /// Retries the authenticate step with exponential backoff when Dexcom
+    /// answers `AuthenticateMaxAttempsExceed`. `sleep` is called with each
+    /// delay since the crate does not own a timer. Without a policy (the
+    /// default) the error is returned immediately.
+    pub fn set_auth_retry<S: FnMut(Duration) + 'a>(&mut self, policy: RetryPolicy, sleep: S) {
+        self.auth_retry = Some((policy, Box::new(sleep)));
+    }
+
+    /// Spaces API calls at least `interval` apart by `now_ms`, the caller's
+    /// current time in epoch milliseconds. A call issued sooner fails with
+    /// `ClientError::RateLimited` and the remaining wait, unless
+    /// `set_rate_limit_sleep` is set. Unlimited by default.
+    ///
+    /// Only a call's first request is limited: the login after the
+    /// authenticate step, a region switch or a query-string retry go out
+    /// right away.
+    pub fn set_min_request_interval<K: Fn() -> i64 + 'a>(&mut self, interval: Duration, now_ms: K) {
+        self.rate_limit = Some(RateLimit {
+            interval,
+            now_ms: Box::new(now_ms),
+            sleep: None,
+            last_request_ms: None,
+            in_operation: false,
+            paid: false,
+        });
+    }
+
+    /// Makes a rate-limited request wait out the interval with `sleep`
+    /// instead of failing. Has no effect without `set_min_request_interval`.
+    pub fn set_rate_limit_sleep<S: FnMut(Duration) + 'a>(&mut self, sleep: S) {
+        if let Some(rate_limit) = &mut self.rate_limit {
+            rate_limit.sleep = Some(Box::new(sleep));
+        }
+    }
+
+    /// Sends `id` as an `X-Request-ID` header on every subsequent request so
+    /// they can be correlated with server-side logs.
+    pub fn set_request_id(&mut self, id: &str) {
+        self.request_id = Some(id.to_owned());
+    }
+
+    /// The id sent with glucose reads: the one passed to the last successful
+    /// login, or the builder's before any login.
+    pub fn application_id(&self) -> &str {
+        &self.application_id
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// URLs for the currently selected region. They change with
+    /// `set_region` and with a successful auto-region fallback, and are
+    /// rooted at the base URL when one was given to the builder.
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints {
+            authenticate: self.endpoint(Region::authenticate_endpoint),
+            login: self.endpoint(Region::login_id_endpoint),
+            login_by_name: self.endpoint(Region::login_name_endpoint),
+            glucose_readings: self.endpoint(Region::glucose_readings_endpoint),
+            data_range: self.endpoint(Region::data_range_endpoint),
+            device_info: self.endpoint(Region::device_info_endpoint),
+        }
+    }
+
+    fn endpoint(&self, endpoint: fn(Region) -> &'static str) -> Cow<'static, str> {
+        let endpoint = endpoint(self.region);
+        match &self.base_url {
+            Some(base_url) => Cow::Owned(url::rebase(endpoint, base_url)),
+            None => Cow::Borrowed(endpoint),
+        }
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// When enabled, a failed login against the configured region is retried
+    /// once against the other region, and on success the instance switches to
+    /// it permanently. The retry happens at most once per instance to avoid
+    /// tripping Dexcom's account lockout.
+    pub fn set_auto_region(&mut self, auto_region: bool) {
+        self.auto_region = auto_region;
+    }
+
+    /// Rejects readings outside `PLAUSIBLE_VALUES` with
+    /// `ClientError::ImplausibleReading` instead of passing them through.
+    /// Off by default.
+    pub fn set_value_validation(&mut self, value_validation: bool) {
+        self.value_validation = value_validation;
+    }
+
+    /// Makes `get_current_glucose_reading` fail with
+    /// `DexcomError::SensorOutOfRange` for a reading clamped at `SENSOR_LOW`
+    /// or `SENSOR_HIGH` with a `RateOutOfRange` trend, for callers
+    /// that must not act on it. The error carries no status, as the reading
+    /// itself arrived fine. Off by default.
+    pub fn set_reject_sentinels(&mut self, reject_sentinels: bool) {
+        self.reject_sentinels = reject_sentinels;
+    }
+
+    /// Appends `transform` to the steps applied to every reading returned by
+    /// `get_current_glucose_reading` and `get_glucose_readings`, after value
+    /// validation. A current reading dropped by a transform is reported like
+    /// an empty response.
+    pub fn add_transform<T: ReadingTransform + 'a>(&mut self, transform: T) {
+        self.transforms.push(Box::new(transform));
+    }
+
+    fn apply_transforms(&self, reading: GlucosReading) -> Option<GlucosReading> {
+        self.transforms
+            .iter()
+            .try_fold(reading, |reading, transform| transform.apply(reading))
+    }
+
+    /// Makes `get_glucose_readings` skip array elements that fail to decode
+    /// instead of failing the whole response; `skipped_readings` reports how
+    /// many were dropped. Off by default.
+    pub fn set_lenient_readings(&mut self, lenient_readings: bool) {
+        self.lenient_readings = lenient_readings;
+    }
+
+    /// Readings the last lenient `get_glucose_readings` skipped.
+    pub fn skipped_readings(&self) -> usize {
+        self.skipped_readings
+    }
+
+    /// Resends a request up to `max_connection_retries` more times when the
+    /// client returns a connection error. Dexcom error responses are never
+    /// retried. Every Share request is a POST that can safely be repeated, so
+    /// this assumes the client reports a failure only when the request
+    /// either never reached the server or can be sent again. Defaults to 0.
+    pub fn set_max_connection_retries(&mut self, max_connection_retries: u32) {
+        self.max_connection_retries = max_connection_retries;
+    }
+
+    /// The `minutes` and `max_count` sent by `get_current_glucose_reading`,
+    /// `MAX_WINDOW_MINUTES` and 1 by default, so the newest reading comes
+    /// back however old it is. Check `GlucosReading::staleness` before acting
+    /// on it, or narrow the window to get an error for old readings instead.
+    pub fn set_default_window(&mut self, minutes: u32, max_count: u32) {
+        self.default_window = (minutes, max_count);
+    }
+
+    /// Supplies the buffer request bodies are serialized into. It is reused
+    /// across requests, so one sized up front avoids allocating per request.
+    pub fn set_request_buffer(&mut self, request_buf: Vec<u8>) {
+        self.request_buf = request_buf;
+    }
+
+    /// Caps the serialized size of a request body, `DEFAULT_MAX_REQUEST_SIZE`
+    /// by default. Serialization stops as soon as the cap is crossed, so an
+    /// oversized field fails with `ClientError::RequestTooLarge` without
+    /// being buffered in full.
+    pub fn set_max_request_size(&mut self, max_request_size: usize) {
+        self.max_request_size = max_request_size;
+    }
+
+    /// Supplies the buffer responses are read into, reused across requests
+    /// like the request buffer. It only reallocates when a request needs
+    /// more than its capacity, e.g. `get_glucose_readings` with a large
+    /// `max_count`.
+    pub fn set_response_buffer(&mut self, response_buf: Vec<u8>) {
+        self.response_buf = response_buf;
+    }
+
+    /// Sends `name: value` with every request, after the built-in headers,
+    /// e.g. an `X-Api-Key` for a proxy in front of Dexcom.
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_owned(), value.to_owned()));
+    }
+
+    /// Decides which HTTP statuses carry a response to decode, for gateways
+    /// that misuse status codes. Other statuses are decoded as errors as
+    /// usual. Defaults to `200..=299`.
+    pub fn set_success_status<F: Fn(u16) -> bool + 'a>(&mut self, success_status: F) {
+        self.success_status = Some(Box::new(success_status));
+    }
+
+    /// Runs `preflight` at the start of every login, before the first Dexcom
+    /// round trip, including `load_account_id`, `detect_region` and logins
+    /// with a stored account id, e.g. to fetch a token for an authenticating
+    /// proxy. The headers it returns replace those of the previous run and
+    /// are sent with every request after the `add_header` ones. An `Err`
+    /// aborts the login with `ClientError::Preflight`.
+    pub fn set_preflight<F>(&mut self, preflight: F)
+    where
+        F: FnMut() -> std::result::Result<Vec<(String, String)>, String> + 'a,
+    {
+        self.preflight = Some(Box::new(preflight));
+    }
+
+    fn run_preflight(&mut self) -> Result<(), C> {
+        if let Some(preflight) = &mut self.preflight {
+            self.preflight_headers = preflight().map_err(ClientError::Preflight)?;
+        }
+        Ok(())
+    }
+
+    /// Overrides the `Content-Type` sent with request bodies, e.g.
+    /// `application/json; charset=utf-8` for gateways that insist on it.
+    pub fn set_content_type(&mut self, content_type: &str) {
+        self.content_type = content_type.to_owned();
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        self.user_agent = user_agent.to_owned();
+    }
+
+    /// Posts `request` as JSON to an arbitrary `uri` and decodes the response
+    /// with the same error handling as the built-in methods, for Share
+    /// endpoints this crate doesn't wrap. Prefer the typed methods where they
+    /// exist.
+    ///
+    /// ```ignore
+    /// #[derive(Serialize)]
+    /// struct DeviceInfoRequest<'a> {
+    ///     #[serde(rename = "sessionId")]
+    ///     session_id: &'a str,
+    /// }
+    ///
+    /// let info: serde_json::Value = dexcom.call(
+    ///     "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherDeviceInfo",
+    ///     &DeviceInfoRequest { session_id: &session_id },
+    /// )?;
+    /// ```
+    pub fn call<S: Serialize, D: DeserializeOwned>(
+        &mut self,
+        uri: &str,
+        request: &S,
+    ) -> Result<D, C> {
+        self.post_request(uri, request)
+    }
+
+    fn post_request<S: Serialize, D: DeserializeOwned>(
+        &mut self,
+        uri: &str,
+        request: &S,
+    ) -> Result<D, C> {
+        self.post_request_sized(uri, request, self.buffer_size)
+    }
+
+    fn post_request_sized<S: Serialize, D: DeserializeOwned>(
+        &mut self,
+        uri: &str,
+        request: &S,
+        buffer_size: usize,
+    ) -> Result<D, C> {
+        self.with_response_buf(buffer_size, |dexcom, buf| {
+            let response = dexcom.send_request(uri, &[], request, buf)?;
+            dexcom.parse_response(&buf[..response.body_len], response.status)
+        })
+    }
+
+    // Lends `response_buf`, zeroed to `size`, for one round trip and puts it
+    // back afterwards, whatever the outcome.
+    fn with_response_buf<T>(
+        &mut self,
+        size: usize,
+        f: impl FnOnce(&mut Self, &mut [u8]) -> T,
+    ) -> T {
+        let mut buf = std::mem::take(&mut self.response_buf);
+        buf.clear();
+        buf.resize(size, 0);
+
+        let result = f(self, &mut buf);
+
+        self.response_buf = buf;
+        result
+    }
+
+    // The authenticate and login endpoints answer with a bare JSON string.
+    // Anything other than a UUID there is rejected, and the all-zero UUID is
+    // Dexcom's way of saying the credentials didn't match.
+    fn post_id_request<S: Serialize>(&mut self, uri: &str, request: &S) -> Result<String, C> {
+        let id: String = self.post_request(uri, request)?;
+        // The body itself is trimmed in `parse_response_with`; some CDNs also
+        // pad the UUID inside the quotes.
+        let id = id.trim().to_owned();
+
+        if id == NIL_UUID {
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(200),
+            ))
+        } else if is_uuid(&id) {
+            Ok(id)
+        } else {
+            Err(ClientError::DexcomError(
+                DexcomError::MalformedId(id),
+                Some(200),
+            ))
+        }
+    }
+
+    fn send_request<S: Serialize>(
+        &mut self,
+        uri: &str,
+        extra_headers: &[(&str, &str)],
+        request: &S,
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, C> {
+        let mut body = std::mem::take(&mut self.request_buf);
+        body.clear();
+
+        let mut writer = BoundedWriter {
+            buf: &mut body,
+            limit: self.max_request_size,
+            exceeded: false,
+        };
+        let serialized = serde_json::to_writer(&mut writer, request);
+        let exceeded = writer.exceeded;
+
+        let result = match serialized {
+            Ok(()) => self.send_body(uri, extra_headers, &body, buf),
+            Err(_) if exceeded => Err(ClientError::RequestTooLarge(self.max_request_size)),
+            Err(error) => Err(SerdeJsonError(error).into()),
+        };
+
+        // Login bodies carry the password; wipe the whole allocation.
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut body);
+
+        self.request_buf = body;
+        result
+    }
+
+    // Takes the field rather than `self` so `send_body` can keep its header
+    // borrows.
+    fn throttle(rate_limit: &mut Option<RateLimit<'a>>) -> Result<(), C> {
+        let Some(rate_limit) = rate_limit else {
+            return Ok(());
+        };
+
+        let follow_up = rate_limit.in_operation && rate_limit.paid;
+        if let Some(last_request_ms) = rate_limit.last_request_ms.filter(|_| !follow_up) {
+            let elapsed_ms = (rate_limit.now_ms)().saturating_sub(last_request_ms);
+            let wait = rate_limit
+                .interval
+                .saturating_sub(Duration::from_millis(elapsed_ms.max(0) as u64));
+
+            if !wait.is_zero() {
+                match &mut rate_limit.sleep {
+                    Some(sleep) => sleep(wait),
+                    None => return Err(ClientError::RateLimited(wait)),
+                }
+            }
+        }
+
+        rate_limit.last_request_ms = Some((rate_limit.now_ms)());
+        rate_limit.paid = true;
+        Ok(())
+    }
+
+    // Marks the start of a call that may take several requests, returning
+    // whether it is the outermost one and must be passed to `end_operation`.
+    pub(crate) fn begin_operation(&mut self) -> bool {
+        match &mut self.rate_limit {
+            Some(rate_limit) if !rate_limit.in_operation => {
+                rate_limit.in_operation = true;
+                rate_limit.paid = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn end_operation(&mut self, outermost: bool) {
+        if let (true, Some(rate_limit)) = (outermost, &mut self.rate_limit) {
+            rate_limit.in_operation = false;
+        }
+    }
+
+    fn operation<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outermost = self.begin_operation();
+        let result = f(self);
+        self.end_operation(outermost);
+        result
+    }
+
+    fn send_body(
+        &mut self,
+        uri: &str,
+        extra_headers: &[(&str, &str)],
+        body: &[u8],
+        buf: &mut [u8],
+    ) -> Result<HttpResponse, C> {
+        #[cfg(feature = "log")]
+        log::info!("POST {} {}", redact::uuids(uri), redact::request_body(body));
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "POST {=str} {=str}",
+            redact::uuids(uri).as_str(),
+            redact::request_body(body).as_str()
+        );
+
+        let mut headers = vec![
+            ("Content-Type", self.content_type.as_str()),
+            ("User-Agent", self.user_agent.as_str()),
+        ];
+        if let Some(request_id) = &self.request_id {
+            headers.push(("X-Request-ID", request_id));
+        }
+        headers.extend(
+            self.headers
+                .iter()
+                .chain(&self.preflight_headers)
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        );
+        headers.extend_from_slice(extra_headers);
+
+        Self::throttle(&mut self.rate_limit)?;
+
+        let mut attempt = 0;
+        let response = loop {
+            match self.client.post_request(uri, &headers, body, buf) {
+                Err(_) if attempt < self.max_connection_retries => attempt += 1,
+                result => break result?,
+            }
+        };
+
+        #[cfg(feature = "log")]
+        log::info!("{}", redact::response_body(&buf[..response.body_len]));
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "{=str}",
+            redact::response_body(&buf[..response.body_len]).as_str()
+        );
+
+        match response.location {
+            Some(location) if (300..=399).contains(&response.status) => {
+                Err(ClientError::Redirect(location))
+            }
+            _ => Ok(response),
+        }
+    }
+
+    fn parse_response<D: DeserializeOwned>(&self, buf: &[u8], status_code: u16) -> Result<D, C> {
+        self.parse_response_with(buf, status_code, |buf| from_slice::<D>(buf))
+    }
+
+    fn parse_response_with<T>(
+        &self,
+        buf: &[u8],
+        status_code: u16,
+        parse: impl FnOnce(&[u8]) -> serde_json::Result<T>,
+    ) -> Result<T, C> {
+        let buf = decode_body(buf).ok_or(ClientError::CompressedResponse)?;
+        let buf = trim_body(buf.as_ref());
+
+        let is_success = match &self.success_status {
+            Some(success_status) => success_status(status_code),
+            None => (200..=299).contains(&status_code),
+        };
 
         match status_code {
-            200..=299 => {
-                let response = serde_json::from_slice::<D>(buf).map_err(SerdeJsonError)?;
+            _ if is_success => {
+                let response = parse(buf).map_err(SerdeJsonError)?;
                 Ok(response)
             }
-            _ => {
-                let response = serde_json::from_slice::<DexcomErrorResponse>(buf)
-                    .map_err(SerdeJsonError)?;
-                let error: DexcomError = response.into();
-                Err(ClientError::DexcomError(error))
-            }
+            503 if is_maintenance_body(buf) => Err(ClientError::DexcomError(
+                DexcomError::UnderMaintenance,
+                Some(status_code),
+            )),
+            503 => match error_response_from_slice(buf) {
+                Some(response) => Err(ClientError::DexcomError(response.into(), Some(status_code))),
+                None => Err(ClientError::DexcomError(
+                    DexcomError::ServerUnavailable,
+                    Some(status_code),
+                )),
+            },
+            _ => match error_response_from_slice(buf) {
+                Some(response) => Err(ClientError::DexcomError(response.into(), Some(status_code))),
+                None => Err(ClientError::HttpStatus(status_code)),
+            },
         }
     }
 
@@ -187,43 +1878,708 @@ impl<'a, C: Client> Dexcom<'a, C> {
         &mut self,
         session_id: &str,
     ) -> Result<GlucosReading, C> {
-        let reading = self.post_request::<_, [GlucosReading; 1]>(
-            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
-            &GetLatestGlucoseValuesRequest {
-                session_id,
-                minutes: 10,
-                max_count: 1,
-            },
-        )?;
+        let reading = self.latest_reading(session_id, &[])?;
+        self.current_reading(reading)
+    }
 
-        Ok(reading[0])
+    // What `get_current_glucose_reading` makes of the latest reading.
+    fn current_reading(&self, reading: Option<GlucosReading>) -> Result<GlucosReading, C> {
+        let reading = reading
+            .and_then(|reading| self.apply_transforms(reading))
+            .ok_or_else(|| SerdeJsonError(de::Error::invalid_length(0, &"a reading")))?;
+
+        if self.reject_sentinels
+            && (reading.is_below_range() || reading.is_above_range())
+            && reading.trend == Trend::RateOutOfRange
+        {
+            return Err(ClientError::DexcomError(
+                DexcomError::SensorOutOfRange,
+                None,
+            ));
+        }
+        Ok(reading)
     }
 
-    pub fn load_session_id(
-        &mut self,
-        account_name: &str,
-        password: &str,
-        application_id: &str,
-    ) -> Result<String, C> {
-        let account_id = self.get_account_id(account_name, password, application_id)?;
-        let session_id = self.get_session_id(&account_id, password, application_id)?;
-        Ok(session_id)
+    // The response buffer for up to `max_count` readings. The server never
+    // returns more than `MAX_COUNT`, so larger counts don't grow it further.
+    fn readings_buffer_size(&self, max_count: u32) -> usize {
+        self.buffer_size
+            .max(max_count.min(MAX_COUNT) as usize * MAX_READING_JSON_LEN)
     }
 
-    fn get_account_id(
+    fn latest_reading(
         &mut self,
-        account_name: &str,
-        password: &str,
-        application_id: &str,
-    ) -> Result<String, C> {
-        self.post_request(
-            url::DEXCOM_AUTHENTICATE_ENDPOINT,
-            &GetAccountIdRequest {
-                account_name,
-                password,
-                application_id,
-            },
-        )
+        session_id: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Option<GlucosReading>, C> {
+        let (minutes, max_count) = self.default_window;
+        let buffer_size = self.readings_buffer_size(max_count);
+
+        let Readings(readings) = self.read_glucose_with(
+            session_id,
+            minutes,
+            max_count,
+            buffer_size,
+            headers,
+            |buf| from_slice(buf),
+        )?;
+        let reading = readings.into_iter().max_by_key(|r| r.wall_time);
+
+        self.validate(reading.as_slice())?;
+        Ok(reading)
+    }
+
+    /// The latest-reading response exactly as the server sent it, for
+    /// debugging or forwarding. Non-2xx responses are returned too rather than
+    /// decoded into errors; only transport failures are errors.
+    pub fn get_current_glucose_reading_raw(&mut self, session_id: &str) -> Result<RawResponse, C> {
+        let uri = self.endpoint(Region::glucose_readings_endpoint);
+        let application_id = self.application_id.clone();
+        let (minutes, max_count) = self.default_window;
+        let buffer_size = self.readings_buffer_size(max_count);
+
+        self.with_response_buf(buffer_size, |dexcom, buf| {
+            let response = dexcom.send_request(
+                &uri,
+                &[],
+                &GetLatestGlucoseValuesRequest {
+                    session_id,
+                    minutes,
+                    max_count,
+                    application_id: &application_id,
+                },
+                buf,
+            )?;
+
+            Ok(RawResponse {
+                status: response.status,
+                body: buf[..response.body_len].to_vec(),
+            })
+        })
+    }
+
+    /// Fetches up to `max_count` readings from the last `minutes` minutes.
+    /// The Share API caps these at 1440 minutes and 288 readings.
+    ///
+    /// The response buffer grows to fit `max_count` readings, up to
+    /// `MAX_COUNT`, when that exceeds the configured buffer size.
+    ///
+    /// Readings are returned newest-first by wall time regardless of the order
+    /// the server sent them in; readings sharing a wall time keep their
+    /// relative order.
+    pub fn get_glucose_readings(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+    ) -> Result<Vec<GlucosReading>, C> {
+        let buffer_size = self.readings_buffer_size(max_count);
+
+        let mut readings = if self.lenient_readings {
+            let LenientReadings { readings, skipped } =
+                self.read_glucose(session_id, minutes, max_count, buffer_size)?;
+            self.skipped_readings = skipped;
+            readings
+        } else {
+            let Readings(readings) =
+                self.read_glucose(session_id, minutes, max_count, buffer_size)?;
+            readings
+        };
+        readings.sort_by_key(|r| std::cmp::Reverse(r.wall_time));
+
+        self.validate(&readings)?;
+        if !self.transforms.is_empty() {
+            readings = readings
+                .into_iter()
+                .filter_map(|reading| self.apply_transforms(reading))
+                .collect();
+        }
+        Ok(readings)
+    }
+
+    /// `get_glucose_readings` returning the readings in `order`. Readings
+    /// sharing a wall time keep the server's relative order either way.
+    pub fn get_glucose_readings_ordered(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+        order: ReadingOrder,
+    ) -> Result<Vec<GlucosReading>, C> {
+        let mut readings = self.get_glucose_readings(session_id, minutes, max_count)?;
+        if order == ReadingOrder::Ascending {
+            readings.sort_by_key(|r| r.wall_time);
+        }
+        Ok(readings)
+    }
+
+    /// `get_glucose_readings` decoding each element into a caller-defined
+    /// type, e.g. to keep fields `GlucosReading` drops. Readings come back in
+    /// the server's order and value validation doesn't apply.
+    pub fn get_glucose_readings_as<D: DeserializeOwned>(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+    ) -> Result<Vec<D>, C> {
+        let buffer_size = self.readings_buffer_size(max_count);
+
+        self.read_glucose(session_id, minutes, max_count, buffer_size)
+    }
+
+    /// Like `get_glucose_readings`, but writes the readings into `out`
+    /// instead of collecting a `Vec`, and returns how many were written.
+    /// When the response holds more than `out.len()` readings, the newest
+    /// ones are kept and the rest skipped, not rejected. `out` only
+    /// bounds what is kept; the response body is still read into a
+    /// heap buffer as for every other request.
+    pub fn get_glucose_readings_into(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+        out: &mut [GlucosReading],
+    ) -> Result<usize, C> {
+        let buffer_size = self.readings_buffer_size(max_count);
+
+        let count =
+            self.read_glucose_with(session_id, minutes, max_count, buffer_size, &[], |buf| {
+                from_slice_seed(buf, ReadingsInto(&mut *out))
+            })?;
+
+        let readings = &mut out[..count];
+        readings.sort_by_key(|r| std::cmp::Reverse(r.wall_time));
+
+        self.validate(readings)?;
+        Ok(count)
+    }
+
+    /// Readings with a wall time in `since_ms..=now_ms`, newest-first and
+    /// deduplicated by wall time.
+    ///
+    /// The Share API only serves windows that end at the present, so there
+    /// is nothing to page through: one request for the minutes since
+    /// `since_ms` covers the span. Spans longer than `MAX_WINDOW_MINUTES` are
+    /// cut to the last day, and at most `MAX_COUNT` readings come back.
+    pub fn get_readings_since(
+        &mut self,
+        session_id: &str,
+        since_ms: i64,
+        now_ms: i64,
+    ) -> Result<Vec<GlucosReading>, C> {
+        let span_minutes = ((now_ms - since_ms).max(0) as u64).div_ceil(60_000);
+        let minutes = span_minutes.clamp(1, MAX_WINDOW_MINUTES as u64) as u32;
+
+        let mut readings = self.get_glucose_readings(session_id, minutes, MAX_COUNT)?;
+        readings.retain(|r| (since_ms..=now_ms).contains(&r.wall_time));
+        readings.dedup_by_key(|r| r.wall_time);
+        Ok(readings)
+    }
+
+    /// Lowest and highest readings of the last `minutes` minutes, `None` when
+    /// the window holds no readings.
+    pub fn get_glucose_extremes(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+    ) -> Result<Option<(GlucosReading, GlucosReading)>, C> {
+        let readings = self.get_glucose_readings(session_id, minutes, MAX_COUNT)?;
+
+        let min = readings.iter().min_by_key(|r| r.value);
+        let max = readings.iter().max_by_key(|r| r.value);
+        Ok(min.zip(max).map(|(min, max)| (*min, *max)))
+    }
+
+    fn validate(&self, readings: &[GlucosReading]) -> Result<(), C> {
+        if !self.value_validation {
+            return Ok(());
+        }
+
+        match readings
+            .iter()
+            .find(|reading| !PLAUSIBLE_VALUES.contains(&reading.value))
+        {
+            Some(reading) => Err(ClientError::ImplausibleReading(reading.value)),
+            None => Ok(()),
+        }
+    }
+
+    // Some regions reject the JSON body form with an `InvalidArgument` about
+    // `sessionId`; those get the same parameters again as a query string, with
+    // `{}` as the body to match the JSON content type. An id that isn't a UUID
+    // really is invalid and isn't retried.
+    fn read_glucose<D: DeserializeOwned>(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+        buffer_size: usize,
+    ) -> Result<D, C> {
+        self.read_glucose_with(session_id, minutes, max_count, buffer_size, &[], |buf| {
+            from_slice::<D>(buf)
+        })
+    }
+
+    fn read_glucose_with<T>(
+        &mut self,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+        buffer_size: usize,
+        headers: &[(&str, &str)],
+        mut parse: impl FnMut(&[u8]) -> serde_json::Result<T>,
+    ) -> Result<T, C> {
+        let uri = self.endpoint(Region::glucose_readings_endpoint);
+        let application_id = self.application_id.clone();
+
+        self.operation(|dexcom| {
+            dexcom.with_response_buf(buffer_size, |dexcom, buf| {
+                let response = dexcom.send_request(
+                    &uri,
+                    headers,
+                    &GetLatestGlucoseValuesRequest {
+                        session_id,
+                        minutes,
+                        max_count,
+                        application_id: &application_id,
+                    },
+                    buf,
+                )?;
+                let result = dexcom.parse_response_with(
+                    &buf[..response.body_len],
+                    response.status,
+                    &mut parse,
+                );
+
+                match result {
+                    Err(ClientError::DexcomError(DexcomError::InvalidSessionId, _))
+                        if is_uuid(session_id) =>
+                    {
+                        let uri = url::with_glucose_query(&uri, session_id, minutes, max_count);
+                        let response = dexcom.send_body(&uri, headers, b"{}", buf)?;
+                        dexcom.parse_response_with(
+                            &buf[..response.body_len],
+                            response.status,
+                            parse,
+                        )
+                    }
+                    result => result,
+                }
+            })
+        })
+    }
+
+    /// Logs each account in turn over the shared client, one full login at a
+    /// time, and returns every account's outcome; a failed login doesn't stop
+    /// the remaining ones.
+    pub fn load_session_ids(
+        &mut self,
+        credentials: &[Credentials],
+    ) -> Vec<(Credentials, Result<String, C>)> {
+        self.operation(|dexcom| {
+            credentials
+                .iter()
+                .map(|credentials| {
+                    let result = dexcom.load_session_id(
+                        &credentials.account_name,
+                        &credentials.password,
+                        &credentials.application_id,
+                    );
+                    (credentials.clone(), result)
+                })
+                .collect()
+        })
+    }
+
+    /// Logs in and fetches the last day of readings from each of `regions`,
+    /// returning the merged readings newest-first with duplicates removed.
+    ///
+    /// Regions whose login or fetch fails are skipped; the last error is only
+    /// returned when every region fails. Each region is a separate login
+    /// attempt, and repeated failed logins count towards Dexcom's account
+    /// lockout, so keep `regions` to the ones the account may live in.
+    pub fn fetch_from_regions(
+        &mut self,
+        credentials: &Credentials,
+        regions: &[Region],
+    ) -> Result<Vec<GlucosReading>, C> {
+        self.operation(|dexcom| dexcom.fetch_from_regions_inner(credentials, regions))
+    }
+
+    fn fetch_from_regions_inner(
+        &mut self,
+        credentials: &Credentials,
+        regions: &[Region],
+    ) -> Result<Vec<GlucosReading>, C> {
+        let original = self.region;
+        let mut readings = Vec::new();
+        let mut last_error = None;
+
+        for region in regions {
+            self.region = *region;
+
+            let result = self
+                .load_session_id(
+                    &credentials.account_name,
+                    &credentials.password,
+                    &credentials.application_id,
+                )
+                .and_then(|session_id| {
+                    self.get_glucose_readings(&session_id, MAX_WINDOW_MINUTES, MAX_COUNT)
+                });
+
+            match result {
+                Ok(fetched) => readings.extend(fetched),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        self.region = original;
+
+        match last_error {
+            Some(error) if readings.is_empty() => Err(error),
+            _ => {
+                readings.sort_by_key(|r| std::cmp::Reverse(r.wall_time));
+                readings.dedup_by_key(|r| r.wall_time);
+                Ok(readings)
+            }
+        }
+    }
+
+    /// Like `get_current_glucose_reading`, but sends `If-Modified-Since` with
+    /// the system time of `last` and returns `None` on `304 Not Modified`.
+    ///
+    /// Dexcom Share does not currently honor conditional requests and always
+    /// answers `200`, in which case the latest reading is returned as usual;
+    /// this only saves bandwidth behind a caching proxy that does.
+    pub fn get_current_glucose_reading_if_modified(
+        &mut self,
+        session_id: &str,
+        last: &GlucosReading,
+    ) -> Result<Option<GlucosReading>, C> {
+        let since = timestamp::to_http_date(last.system_time);
+
+        match self.latest_reading(session_id, &[("If-Modified-Since", &since)]) {
+            Err(ClientError::HttpStatus(304)) => Ok(None),
+            reading => self.current_reading(reading?).map(Some),
+        }
+    }
+
+    /// Fetches the span of glucose data stored for the session's account, e.g.
+    /// to know how far back history can be paged.
+    pub fn get_data_range(&mut self, session_id: &str) -> Result<DataRange, C> {
+        self.post_request(
+            &self.endpoint(Region::data_range_endpoint),
+            &SessionIdRequest { session_id },
+        )
+    }
+
+    /// Fetches the transmitter id, sensor start time and device model, e.g.
+    /// to show the sensor session's age.
+    ///
+    /// Experimental and unverified: the `ReadPublisherDeviceInfo` path and
+    /// the `TransmitterId`, `SensorStartTime` and `DeviceModel` fields are
+    /// not documented by Dexcom and haven't been confirmed against the live
+    /// service, so this may fail with an HTTP or JSON error, and may change
+    /// or go away in a minor release. `call` reaches other endpoints in the
+    /// meantime.
+    pub fn get_device_info(&mut self, session_id: &str) -> Result<DeviceInfo, C> {
+        self.post_request(
+            &self.endpoint(Region::device_info_endpoint),
+            &SessionIdRequest { session_id },
+        )
+    }
+
+    /// Like `get_current_glucose_reading`, but reports an empty window and
+    /// sensor errors as states instead of errors.
+    pub fn get_reading_state(&mut self, session_id: &str) -> Result<ReadingState, C> {
+        match self.latest_reading(session_id, &[]) {
+            Ok(Some(reading)) => Ok(ReadingState::Available(reading)),
+            Ok(None) => Ok(ReadingState::WarmingUp),
+            Err(ClientError::DexcomError(error, _)) if is_sensor_error(&error) => {
+                Ok(ReadingState::NoSensor)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Fetches the latest reading and returns `None` unless its wall time is
+    /// newer than `last_seen_ms`. The request is still made every time; this
+    /// only spares the caller from processing a reading it already has.
+    pub fn get_current_glucose_reading_if_newer(
+        &mut self,
+        session_id: &str,
+        last_seen_ms: i64,
+    ) -> Result<Option<GlucosReading>, C> {
+        let reading = self.get_current_glucose_reading(session_id)?;
+        Ok(Some(reading).filter(|reading| reading.wall_time > last_seen_ms))
+    }
+
+    /// Probes `session_id` with a one-minute, single-reading request whose
+    /// body is not decoded. `false` when Dexcom reports the session missing
+    /// or expired; any other error is returned.
+    pub fn is_session_valid(&mut self, session_id: &str) -> Result<bool, C> {
+        let result = self.read_glucose_with(session_id, 1, 1, self.buffer_size, &[], |buf| {
+            from_slice::<de::IgnoredAny>(buf)
+        });
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(ClientError::DexcomError(error, _)) if error.is_session_error() => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Performs the authenticate and login round trips and discards the
+    /// resulting session id, e.g. for a "Test Connection" button.
+    pub fn verify_credentials(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<(), C> {
+        self.load_session_id(account_name, password, application_id)
+            .map(|_| ())
+    }
+
+    pub fn load_session_id(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<String, C> {
+        self.load_session_id_with_progress(account_name, password, application_id, |_| {})
+    }
+
+    /// `load_session_id` stamped with `now_ms`, the caller's current time in
+    /// epoch milliseconds, so the session can be refreshed before it expires.
+    pub fn load_session(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+        now_ms: i64,
+    ) -> Result<Session, C> {
+        let id = self.load_session_id(account_name, password, application_id)?;
+        Ok(Session {
+            id,
+            obtained_at_ms: now_ms,
+        })
+    }
+
+    /// `load_session_id` that reports each network round trip to `on_stage`
+    /// before starting it, e.g. for a progress UI. A region switch under
+    /// `set_auto_region` repeats both stages.
+    pub fn load_session_id_with_progress<F: FnMut(LoginStage)>(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+        mut on_stage: F,
+    ) -> Result<String, C> {
+        self.run_preflight()?;
+
+        self.client.begin_session();
+        let result = self.operation(|dexcom| {
+            dexcom.login_with_region_fallback(account_name, password, application_id, &mut on_stage)
+        });
+        self.client.end_session();
+        result
+    }
+
+    fn login_with_region_fallback(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+        on_stage: &mut dyn FnMut(LoginStage),
+    ) -> Result<String, C> {
+        let result = self.login(account_name, password, application_id, on_stage);
+
+        match result {
+            Err(ClientError::DexcomError(ref error, _))
+                if self.auto_region && is_region_mismatch(error) =>
+            {
+                self.auto_region = false;
+                self.region = self.region.other();
+
+                let result = self.login(account_name, password, application_id, on_stage);
+                if result.is_err() {
+                    self.region = self.region.other();
+                }
+                result
+            }
+            result => result,
+        }
+    }
+
+    /// Logs in with the account name in a single round trip through
+    /// `LoginPublisherAccountByName`, skipping the account id lookup.
+    /// Unlike `load_session_id` this neither retries the authenticate step
+    /// nor switches regions.
+    pub fn login_by_name(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<String, C> {
+        Self::check_not_empty([
+            (account_name, DexcomError::InvalidUsername),
+            (password, DexcomError::InvalidPassword),
+            (application_id, DexcomError::InvalidApplicationId),
+        ])?;
+        self.run_preflight()?;
+
+        let session_id = self.post_id_request(
+            &self.endpoint(Region::login_name_endpoint),
+            &GetAccountIdRequest {
+                account_name,
+                password,
+                application_id,
+            },
+        )?;
+        self.application_id = application_id.to_owned();
+        Ok(session_id)
+    }
+
+    /// Logs in with `login_by_name` and reads the current reading, two round
+    /// trips from a cold start. The session id is discarded; login errors are
+    /// returned as is, so e.g. a wrong password still surfaces as
+    /// `AccountPasswordInvalid`.
+    pub fn quick_read(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<GlucosReading, C> {
+        self.operation(|dexcom| {
+            let session_id = dexcom.login_by_name(account_name, password, application_id)?;
+            dexcom.get_current_glucose_reading(&session_id)
+        })
+    }
+
+    /// Finds the region the account lives in by running the authenticate step
+    /// against the configured region and then the other one. The configured
+    /// region is left unchanged, so pass the result to `set_region`.
+    ///
+    /// When both regions reject the credentials the last rejection is
+    /// returned, e.g. `AccountPasswordInvalid` for a wrong password. Other
+    /// errors end the search right away. Each call costs up to two login
+    /// attempts against Dexcom's lockout counter.
+    pub fn detect_region(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<Region, C> {
+        self.run_preflight()?;
+
+        let original = self.region;
+        let mut result = Ok(original);
+
+        self.operation(|dexcom| {
+            for region in [original, original.other()] {
+                dexcom.region = region;
+                result = dexcom
+                    .get_account_id(account_name, password, application_id)
+                    .map(|_| region);
+
+                match &result {
+                    Err(ClientError::DexcomError(error, _)) if is_region_mismatch(error) => {}
+                    _ => break,
+                }
+            }
+        });
+
+        self.region = original;
+        result
+    }
+
+    /// Runs only the authenticate step, returning the account id so it can be
+    /// stored for `load_session_id_with_account_id`.
+    pub fn load_account_id(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<String, C> {
+        self.run_preflight()?;
+        self.get_account_id(account_name, password, application_id)
+    }
+
+    /// Logs in with a previously stored account id, skipping the authenticate
+    /// step.
+    ///
+    /// A stale account id fails with `DexcomError::InvalidAccountId`; callers
+    /// should then fall back to the full `load_session_id`.
+    pub fn load_session_id_with_account_id(
+        &mut self,
+        account_id: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<String, C> {
+        self.run_preflight()?;
+        self.get_session_id(account_id, password, application_id)
+    }
+
+    fn login(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+        on_stage: &mut dyn FnMut(LoginStage),
+    ) -> Result<String, C> {
+        on_stage(LoginStage::Authenticating);
+        let account_id = self.get_account_id(account_name, password, application_id)?;
+        on_stage(LoginStage::FetchingSession);
+        let session_id = self.get_session_id(&account_id, password, application_id)?;
+        Ok(session_id)
+    }
+
+    // Dexcom answers empty login fields with an `InvalidArgument` that often
+    // doesn't say which one, so they are rejected before any round trip.
+    fn check_not_empty<const N: usize>(fields: [(&str, DexcomError); N]) -> Result<(), C> {
+        match fields.into_iter().find(|(value, _)| value.is_empty()) {
+            Some((_, error)) => Err(ClientError::DexcomError(error, None)),
+            None => Ok(()),
+        }
+    }
+
+    fn get_account_id(
+        &mut self,
+        account_name: &str,
+        password: &str,
+        application_id: &str,
+    ) -> Result<String, C> {
+        Self::check_not_empty([
+            (account_name, DexcomError::InvalidUsername),
+            (password, DexcomError::InvalidPassword),
+            (application_id, DexcomError::InvalidApplicationId),
+        ])?;
+
+        let mut retry = 0;
+
+        loop {
+            let result = self.post_id_request(
+                &self.endpoint(Region::authenticate_endpoint),
+                &GetAccountIdRequest {
+                    account_name,
+                    password,
+                    application_id,
+                },
+            );
+
+            match (&result, &mut self.auth_retry) {
+                (
+                    Err(ClientError::DexcomError(DexcomError::AuthenticateMaxAttempsExceed, _)),
+                    Some((policy, sleep)),
+                ) if retry < policy.max_retries => {
+                    sleep(policy.delay(retry));
+                    retry += 1;
+                }
+                _ => return result,
+            }
+        }
     }
 
     fn get_session_id(
@@ -232,108 +2588,2721 @@ impl<'a, C: Client> Dexcom<'a, C> {
         password: &str,
         application_id: &str,
     ) -> Result<String, C> {
-        self.post_request(
-            url::DEXCOM_LOGIN_ID_ENDPOINT,
+        Self::check_not_empty([
+            (account_id, DexcomError::InvalidAccountId),
+            (password, DexcomError::InvalidPassword),
+            (application_id, DexcomError::InvalidApplicationId),
+        ])?;
+
+        let session_id = self.post_id_request(
+            &self.endpoint(Region::login_id_endpoint),
             &GetSessionIdRequest {
                 account_id,
                 password,
                 application_id,
             },
+        )?;
+        // Reads must carry the id the session was obtained with.
+        self.application_id = application_id.to_owned();
+        Ok(session_id)
+    }
+}
+
+mod url {
+    use crate::builder::BuildError;
+
+    pub(crate) mod ous {
+        pub(crate) const DEXCOM_GLUCOSE_READINGS_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues";
+        pub(crate) const DEXCOM_DATA_RANGE_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherGlucoseDataRange";
+        pub(crate) const DEXCOM_DEVICE_INFO_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherDeviceInfo";
+        pub(crate) const DEXCOM_LOGIN_ID_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountById";
+        pub(crate) const DEXCOM_LOGIN_NAME_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountByName";
+        pub(crate) const DEXCOM_AUTHENTICATE_ENDPOINT: &str =
+            "https://shareous1.dexcom.com/ShareWebServices/Services/General/AuthenticatePublisherAccount";
+    }
+
+    pub(crate) mod us {
+        pub(crate) const DEXCOM_GLUCOSE_READINGS_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues";
+        pub(crate) const DEXCOM_DATA_RANGE_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherGlucoseDataRange";
+        pub(crate) const DEXCOM_DEVICE_INFO_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherDeviceInfo";
+        pub(crate) const DEXCOM_LOGIN_ID_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountById";
+        pub(crate) const DEXCOM_LOGIN_NAME_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountByName";
+        pub(crate) const DEXCOM_AUTHENTICATE_ENDPOINT: &str =
+            "https://share2.dexcom.com/ShareWebServices/Services/General/AuthenticatePublisherAccount";
+    }
+
+    /// Replaces the scheme and host of `endpoint` with `base_url`, which
+    /// has already been validated and normalized by the builder.
+    pub(crate) fn rebase(endpoint: &str, base_url: &str) -> String {
+        let path = endpoint
+            .strip_prefix("https://")
+            .and_then(|rest| rest.find('/').map(|index| &rest[index..]))
+            .unwrap_or(endpoint);
+        format!("{}{}", base_url, path)
+    }
+
+    /// Checks that `base_url` is an absolute `https` URL without a query or
+    /// fragment and strips trailing slashes. Plain `http` is refused since
+    /// the credentials would travel in the clear.
+    pub(crate) fn normalize_base_url(base_url: &str) -> Result<String, BuildError> {
+        if base_url.starts_with("http://") {
+            return Err(BuildError::InsecureBaseUrl);
+        }
+
+        let base_url = base_url.trim_end_matches('/');
+        let host = base_url
+            .strip_prefix("https://")
+            .ok_or(BuildError::InvalidBaseUrl)?;
+        let host = host.split('/').next().unwrap_or_default();
+
+        if host.is_empty()
+            || base_url.contains(['?', '#'])
+            || base_url.contains(|c: char| c.is_whitespace() || c.is_control())
+        {
+            return Err(BuildError::InvalidBaseUrl);
+        }
+
+        Ok(base_url.to_owned())
+    }
+
+    pub(crate) fn with_glucose_query(
+        endpoint: &str,
+        session_id: &str,
+        minutes: u32,
+        max_count: u32,
+    ) -> String {
+        format!(
+            "{}?sessionId={}&minutes={}&maxCount={}",
+            endpoint,
+            encode_query_value(session_id),
+            minutes,
+            max_count
         )
     }
-}
 
-#[cfg(feature = "ous")]
-mod url {
-    pub(crate) const DEXCOM_GLUCOSE_READINGS_ENDPOINT: &str = 
-        "https://shareous1.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues";
-    pub(crate) const DEXCOM_LOGIN_ID_ENDPOINT: &str =
-        "https://shareous1.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountById";
-    pub(crate) const DEXCOM_AUTHENTICATE_ENDPOINT: &str =
-        "https://shareous1.dexcom.com/ShareWebServices/Services/General/AuthenticatePublisherAccount";
-}
+    fn encode_query_value(value: &str) -> String {
+        value
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{:02X}", byte),
+            })
+            .collect()
+    }
+
+    #[cfg(all(test, feature = "ous"))]
+    pub(crate) use ous::*;
+
+    #[cfg(all(test, not(feature = "ous")))]
+    pub(crate) use us::*;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use mockall::predicate::*;
+
+    use super::*;
+    use super::client::*;
+    use crate::testing::*;
+
+    #[test]
+    fn test_get_current_glucose_reading() {
+        let mut client = mock_client();
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::DEXCOM_AUTHENTICATE_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::DEXCOM_LOGIN_ID_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"")
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::DEXCOM_GLUCOSE_READINGS_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(format!("[{}]", reading_json(153, 1699110415000)).as_bytes())
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+
+        let session_id = dexcom.load_session_id("user", "pass", "app").unwrap();
+        assert_eq!(session_id, "a21d18db-a276-40bc-8337-77dcd02df53e");
+
+        let glucose = dexcom.get_current_glucose_reading(&session_id);
+
+        assert!(glucose.is_ok());
+        assert_eq!(
+            glucose.unwrap(),
+            GlucosReading {
+                trend: Trend::Flat,
+                value: 153,
+                wall_time: 1699110415000,
+                system_time: 1699110415000,
+                display_time: 1699110415000,
+                source: Source::DexcomShare,
+            }
+        )
+    }
+
+    #[test]
+    fn test_is_session_valid() {
+        let mut client = mock_client();
+        let mut calls = 0;
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body.ends_with(br#""minutes":1,"maxCount":1,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#)
+            })
+            .times(4)
+            .returning(move |_, _, _, mut buf| {
+                calls += 1;
+                let (status, body) = match calls {
+                    1 => (200, "[]"),
+                    2 => (500, r#"{"Code":"SessionNotValid"}"#),
+                    3 => (
+                        500,
+                        r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: sessionId"}"#,
+                    ),
+                    _ => return Err(TestError),
+                };
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let session_id = "a21d18db-a276-40bc-8337-77dcd02df53e";
+        assert!(dexcom.is_session_valid(session_id).unwrap());
+        assert!(!dexcom.is_session_valid(session_id).unwrap());
+        assert!(!dexcom.is_session_valid("").unwrap());
+        assert!(matches!(
+            dexcom.is_session_valid(session_id),
+            Err(ClientError::ConnectionError(TestError))
+        ));
+    }
+
+    #[test]
+    fn test_login_by_name() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_LOGIN_NAME_ENDPOINT
+                    && body == br#"{"accountName":"user","password":"pass","applicationId":"app"}"#
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom.login_by_name("user", "pass", "app").unwrap(),
+            "a21d18db-a276-40bc-8337-77dcd02df53e"
+        );
+    }
+
+    #[test]
+    fn test_quick_read() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_NAME_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body.starts_with(br#"{"sessionId":"a21d18db-a276-40bc-8337-77dcd02df53e""#)
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let body = format!("[{}]", reading_json(153, 1699110415000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(dexcom.quick_read("user", "pass", "app").unwrap().value, 153);
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_NAME_ENDPOINT,
+            500,
+            r#"{"Code":"AccountPasswordInvalid"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom.quick_read("user", "wrong", "app"),
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(500)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bom_prefixed_response() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            "\u{feff} [{\"WT\":\"Date(1699110415000)\",\"ST\":\"Date(1699110415000)\",\"DT\":\"Date(1699110415000+0900)\",\"Value\":153,\"Trend\":\"Flat\"}]\r\n",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let reading = dexcom
+            .get_current_glucose_reading("a21d18db-a276-40bc-8337-77dcd02df53e")
+            .unwrap();
+        assert_eq!(reading.value, 153);
+    }
+
+    #[test]
+    fn test_shared_client() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Monitor {
+            dexcom: Dexcom<'static, Rc<RefCell<MockClient>>>,
+        }
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!("[{}]", reading_json(153, 1699110415000)),
+        );
+        let client = Rc::new(RefCell::new(client));
+
+        let mut monitor = Monitor {
+            dexcom: Dexcom::with_client(Rc::clone(&client)),
+        };
+        let mut other = Dexcom::with_client(client);
+
+        let session_id = "a21d18db-a276-40bc-8337-77dcd02df53e";
+        assert_eq!(
+            monitor
+                .dexcom
+                .get_current_glucose_reading(session_id)
+                .unwrap()
+                .value,
+            153
+        );
+        assert!(other.get_current_glucose_reading(session_id).is_ok());
+    }
+
+    #[test]
+    fn test_value_validation() {
+        for value in [0, -5, 700] {
+            let body = format!("[{}]", reading_json(value, 1699110415000));
+            let mut client = mock_client();
+            client
+                .expect_post_request()
+                .returning(move |_, _, _, mut buf| {
+                    Ok(HttpResponse {
+                        body_len: buf.write(body.as_bytes()).unwrap(),
+                        status: 200,
+                        location: None,
+                    })
+                });
+
+            let mut dexcom = Dexcom::new(&mut client);
+            assert_eq!(dexcom.get_current_glucose_reading("").unwrap().value, value);
+
+            dexcom.set_value_validation(true);
+            assert!(matches!(
+                dexcom.get_current_glucose_reading(""),
+                Err(ClientError::ImplausibleReading(v)) if v == value
+            ));
+        }
+    }
+
+    #[test]
+    fn test_min_request_interval() {
+        use std::cell::Cell;
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+        let account_id = "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8";
+
+        let now = Cell::new(0);
+        let slept = Cell::new(Duration::ZERO);
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_min_request_interval(Duration::from_secs(60), || now.get());
+
+        assert!(dexcom
+            .load_session_id_with_account_id(account_id, "pass", "app")
+            .is_ok());
+        now.set(45_000);
+        assert_eq!(
+            dexcom.load_session_id_with_account_id(account_id, "pass", "app"),
+            Err(ClientError::RateLimited(Duration::from_secs(15)))
+        );
+        now.set(60_000);
+        assert!(dexcom
+            .load_session_id_with_account_id(account_id, "pass", "app")
+            .is_ok());
+
+        dexcom.set_min_request_interval(Duration::from_secs(60), || now.get());
+        dexcom.set_rate_limit_sleep(|wait| {
+            slept.set(wait);
+            now.set(now.get() + wait.as_millis() as i64);
+        });
+
+        assert!(dexcom
+            .load_session_id_with_account_id(account_id, "pass", "app")
+            .is_ok());
+        now.set(70_000);
+        assert!(dexcom
+            .load_session_id_with_account_id(account_id, "pass", "app")
+            .is_ok());
+        assert_eq!(slept.get(), Duration::from_secs(50));
+    }
+
+    #[test]
+    fn test_min_request_interval_spans_login() {
+        use std::cell::Cell;
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let now = Cell::new(0);
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_min_request_interval(Duration::from_secs(60), || now.get());
+
+        assert_eq!(
+            dexcom.load_session_id("user", "pass", "app").unwrap(),
+            "a21d18db-a276-40bc-8337-77dcd02df53e"
+        );
+        now.set(1_000);
+        assert_eq!(
+            dexcom.load_session_id("user", "pass", "app"),
+            Err(ClientError::RateLimited(Duration::from_secs(59)))
+        );
+        now.set(61_000);
+        assert!(dexcom.verify_credentials("user", "pass", "app").is_ok());
+    }
+
+    #[test]
+    fn test_max_request_size() {
+        let mut client = mock_client();
+        client.expect_post_request().never();
+
+        let password = "p".repeat(DEFAULT_MAX_REQUEST_SIZE);
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom.load_session_id("user", &password, "app"),
+            Err(ClientError::RequestTooLarge(DEFAULT_MAX_REQUEST_SIZE))
+        );
+
+        dexcom.set_max_request_size(16);
+        assert_eq!(
+            dexcom.load_session_id("user", "pass", "app"),
+            Err(ClientError::RequestTooLarge(16))
+        );
+    }
+
+    #[test]
+    fn test_into_boxed() {
+        let json_error = serde_json::from_str::<GlucosReading>("[").unwrap_err();
+        let errors: [ClientError<TestError>; 9] = [
+            ClientError::ConnectionError(TestError),
+            ClientError::DexcomError(DexcomError::SessionNotFound, Some(500)),
+            ClientError::DexcomError(DexcomError::InvalidPassword, None),
+            ClientError::JSONError(SerdeJsonError(json_error)),
+            ClientError::ImplausibleReading(700),
+            ClientError::CompressedResponse,
+            ClientError::HttpStatus(502),
+            ClientError::RequestTooLarge(1024),
+            ClientError::RateLimited(Duration::from_secs(15)),
+        ];
+        let messages = [
+            "connection error: TestError",
+            "session not found (HTTP 500)",
+            "invalid password",
+            "invalid JSON response: EOF while parsing a list at line 1 column 1",
+            "implausible reading 700",
+            "response body is compressed",
+            "unexpected HTTP status 502",
+            "request body exceeds 1024 bytes",
+            "rate limited, retry in 15s",
+        ];
+
+        for (error, message) in errors.into_iter().zip(messages) {
+            let boxed = error.into_boxed();
+            assert_eq!(boxed.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_client_error_eq() {
+        type Error = ClientError<TestError>;
+
+        assert_eq!(
+            Error::DexcomError(DexcomError::SessionNotFound, Some(500)),
+            Error::DexcomError(DexcomError::SessionNotFound, Some(500))
+        );
+        assert_ne!(
+            Error::DexcomError(DexcomError::SessionNotFound, Some(500)),
+            Error::DexcomError(DexcomError::SessionInvalid, Some(500))
+        );
+
+        let json = |body: &str| {
+            Error::JSONError(SerdeJsonError(
+                serde_json::from_str::<GlucosReading>(body).unwrap_err(),
+            ))
+        };
+        assert_eq!(json("[1"), json("[2"));
+        assert_ne!(json("[1"), json("{"));
+    }
+
+    #[test]
+    fn test_reject_sentinels() {
+        for (value, rejected) in [(40, true), (400, true), (153, false)] {
+            let body = format!(
+                r#"[{{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":{},"Trend":"RateOutOfRange"}}]"#,
+                value
+            );
+            let mut client = mock_client();
+            client
+                .expect_post_request()
+                .returning(move |_, _, _, mut buf| {
+                    Ok(HttpResponse {
+                        body_len: buf.write(body.as_bytes()).unwrap(),
+                        status: 200,
+                        location: None,
+                    })
+                });
+
+            let mut dexcom = Dexcom::new(&mut client);
+            assert_eq!(dexcom.get_current_glucose_reading("").unwrap().value, value);
+
+            dexcom.set_reject_sentinels(true);
+            let result = dexcom.get_current_glucose_reading("");
+            if rejected {
+                assert!(matches!(
+                    result,
+                    Err(ClientError::DexcomError(
+                        DexcomError::SensorOutOfRange,
+                        None
+                    ))
+                ));
+            } else {
+                assert_eq!(result.unwrap().value, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_data_range() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_DATA_RANGE_ENDPOINT,
+            200,
+            r#"{"Start":"Date(1698505615000+0900)","End":"Date(1699110415000+0900)"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom.get_data_range("").unwrap(),
+            DataRange {
+                start: 1698505615000,
+                end: 1699110415000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_device_info() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_DEVICE_INFO_ENDPOINT
+                    && body == br#"{"sessionId":"a21d18db-a276-40bc-8337-77dcd02df53e"}"#
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let body = br#"{"TransmitterId":"8G1234","SensorStartTime":"Date(1698505615000+0900)","DeviceModel":"G6"}"#;
+                Ok(HttpResponse {
+                    body_len: buf.write(body).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom
+                .get_device_info("a21d18db-a276-40bc-8337-77dcd02df53e")
+                .unwrap(),
+            DeviceInfo {
+                transmitter_id: "8G1234".to_owned(),
+                sensor_start_time: 1698505615000,
+                device_model: "G6".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_request_buffer_is_reused() {
+        let bodies = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut client = mock_client();
+        let seen = bodies.clone();
+        client
+            .expect_post_request()
+            .returning(move |_, _, body, mut buf| {
+                seen.lock().unwrap().push(body.as_ptr() as usize);
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let request_buf = Vec::with_capacity(256);
+        let ptr = request_buf.as_ptr() as usize;
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_request_buffer(request_buf);
+        dexcom.verify_credentials("user", "pass", "app").unwrap();
+        dexcom.verify_credentials("user", "pass", "app").unwrap();
+
+        assert_eq!(*bodies.lock().unwrap(), [ptr; 4]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_response() {
+        use flate2::{write::GzEncoder, Compression};
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(format!("[{}]", reading_json(153, 1699110415000)).as_bytes())
+            .unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .returning(move |_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf.write(&body).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(dexcom.get_current_glucose_reading("").unwrap().value, 153);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_response() {
+        let mut client = mock_client();
+        client.expect_post_request().returning(|_, _, _, mut buf| {
+            Ok(HttpResponse {
+                body_len: buf.write(&[0x1f, 0x8b, 0x08, 0x00]).unwrap(),
+                status: 200,
+                location: None,
+            })
+        });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.get_current_glucose_reading(""),
+            Err(ClientError::CompressedResponse)
+        ));
+    }
+
+    #[test]
+    fn test_get_current_glucose_reading_raw() {
+        for (status, fixture) in [
+            (200, format!("[{}]", reading_json(153, 1699110415000))),
+            (500, r#"{"Code":"SessionNotValid"}"#.to_owned()),
+        ] {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                status,
+                fixture.as_str(),
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            assert_eq!(
+                dexcom.get_current_glucose_reading_raw("").unwrap(),
+                RawResponse {
+                    status,
+                    body: fixture.as_bytes().to_vec(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_glucose_query_fallback() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            500,
+            r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: sessionId","TypeName":"ArgumentException"}"#,
+        );
+
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::with_glucose_query(
+                    url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                    "a21d18db-a276-40bc-8337-77dcd02df53e",
+                    MAX_WINDOW_MINUTES,
+                    1,
+                ) && body == b"{}"
+            })
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(format!("[{}]", reading_json(153, 1699110415000)).as_bytes())
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let reading = dexcom
+            .get_current_glucose_reading("a21d18db-a276-40bc-8337-77dcd02df53e")
+            .unwrap();
+
+        assert_eq!(reading.value, 153);
+    }
+
+    #[test]
+    fn test_glucose_query_fallback_needs_well_formed_id() {
+        for session_id in ["", "not-a-session"] {
+            let mut client = mock_client();
+            client
+                .expect_post_request()
+                .times(1)
+                .returning(|_, _, _, mut buf| {
+                    let body = br#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: sessionId","TypeName":"ArgumentException"}"#;
+                    Ok(HttpResponse {
+                        body_len: buf.write(body).unwrap(),
+                        status: 500,
+                        location: None,
+                    })
+                });
+
+            let mut dexcom = Dexcom::new(&mut client);
+            assert!(matches!(
+                dexcom.get_current_glucose_reading(session_id),
+                Err(ClientError::DexcomError(
+                    DexcomError::InvalidSessionId,
+                    Some(500)
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_with_glucose_query() {
+        assert_eq!(
+            url::with_glucose_query(
+                url::us::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                "a21d18db-a276-40bc-8337-77dcd02df53e",
+                10,
+                1
+            ),
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues?sessionId=a21d18db-a276-40bc-8337-77dcd02df53e&minutes=10&maxCount=1"
+        );
+        assert_eq!(
+            url::with_glucose_query("https://example.com/read", "a b&c", 5, 2),
+            "https://example.com/read?sessionId=a%20b%26c&minutes=5&maxCount=2"
+        );
+    }
+
+    #[test]
+    fn test_get_current_glucose_reading_not_modified() {
+        let mut client = mock_client();
+
+        client
+            .expect_post_request()
+            .withf(|uri, headers, _, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && headers.contains(&("If-Modified-Since", "Sat, 04 Nov 2023 15:06:55 GMT"))
+            })
+            .returning(|_, _, _, _| {
+                Ok(HttpResponse {
+                    body_len: 0,
+                    status: 304,
+                    location: None,
+                })
+            });
+
+        let last = GlucosReading {
+            trend: Trend::Flat,
+            value: 153,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let reading = dexcom.get_current_glucose_reading_if_modified("", &last);
+
+        assert_eq!(reading.unwrap(), None);
+    }
+
+    #[test]
+    fn test_if_modified_decodes_like_current_reading() {
+        let bodies = [
+            r#"{"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":152,"Trend":"None"}"#,
+            r#"[
+                {"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":140,"Trend":"Flat"},
+                {"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":152,"Trend":"None"}
+            ]"#,
+        ];
+        let last = GlucosReading::with_wall_time(140, Trend::Flat, 1699110415000);
+
+        for body in bodies {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                200,
+                body,
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            dexcom.add_transform(transform::FillTrend(Trend::Flat));
+            let reading = dexcom
+                .get_current_glucose_reading_if_modified("", &last)
+                .unwrap()
+                .unwrap();
+            assert_eq!((reading.value, reading.trend), (152, Trend::Flat));
+        }
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            r#"[{"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":400,"Trend":"RateOutOfRange"}]"#,
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_reject_sentinels(true);
+        assert!(matches!(
+            dexcom.get_current_glucose_reading_if_modified("", &last),
+            Err(ClientError::DexcomError(
+                DexcomError::SensorOutOfRange,
+                None
+            ))
+        ));
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            "[]",
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.get_current_glucose_reading_if_modified("", &last),
+            Err(ClientError::JSONError(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_current_glucose_reading_if_newer() {
+        for (last_seen_ms, newer) in [
+            (1699110115000, true),
+            (1699110415000, false),
+            (1699110715000, false),
+        ] {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                200,
+                format!("[{}]", reading_json(153, 1699110415000)),
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            let reading = dexcom
+                .get_current_glucose_reading_if_newer("", last_seen_ms)
+                .unwrap();
+
+            assert_eq!(reading.is_some(), newer, "{}", last_seen_ms);
+        }
+    }
+
+    #[test]
+    fn test_detect_region() {
+        const ACCOUNT_ID: &str = "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"";
+        const REJECTED: &str = r#"{"Code":"AccountPasswordInvalid"}"#;
+
+        for (us, ous, expected) in [
+            ((200, ACCOUNT_ID), (500, REJECTED), Ok(Region::Us)),
+            ((500, REJECTED), (200, ACCOUNT_ID), Ok(Region::Ous)),
+            (
+                (500, REJECTED),
+                (500, REJECTED),
+                Err(ClientError::DexcomError(
+                    DexcomError::AccountPasswordInvalid,
+                    Some(500),
+                )),
+            ),
+        ] {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::us::DEXCOM_AUTHENTICATE_ENDPOINT,
+                us.0,
+                us.1,
+            );
+            expect_response(
+                &mut client,
+                url::ous::DEXCOM_AUTHENTICATE_ENDPOINT,
+                ous.0,
+                ous.1,
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            let region = dexcom.region();
+
+            assert_eq!(dexcom.detect_region("user", "pass", "app"), expected);
+            assert_eq!(dexcom.region(), region);
+        }
+    }
+
+    #[test]
+    fn test_auto_region_switches_to_ous() {
+        let mut client = mock_client();
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::us::DEXCOM_AUTHENTICATE_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let size = buf.write(br#"{"Code":"AccountPasswordInvalid"}"#).unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 500,
+                    location: None,
+                })
+            });
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::ous::DEXCOM_AUTHENTICATE_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        client
+            .expect_post_request()
+            .with(
+                eq(url::ous::DEXCOM_LOGIN_ID_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let size = buf
+                    .write(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"")
+                    .unwrap();
+                Ok(HttpResponse {
+                    body_len: size,
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_region(Region::Us);
+        dexcom.set_auto_region(true);
+
+        let session_id = dexcom.load_session_id("user", "pass", "app").unwrap();
+
+        assert_eq!(session_id, "a21d18db-a276-40bc-8337-77dcd02df53e");
+        assert_eq!(dexcom.region(), Region::Ous);
+    }
+
+    #[test]
+    fn test_verify_credentials() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(dexcom.verify_credentials("user", "pass", "app").is_ok());
+    }
+
+    #[test]
+    fn test_whitespace_padded_ids() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            " \"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"\r\n",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\\n\"\n",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom.load_account_id("user", "pass", "app").unwrap(),
+            "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8"
+        );
+        assert_eq!(
+            dexcom.load_session_id("user", "pass", "app").unwrap(),
+            "a21d18db-a276-40bc-8337-77dcd02df53e"
+        );
+    }
+
+    #[test]
+    fn test_load_session() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom
+                .load_session("user", "pass", "app", 1699110415000)
+                .unwrap(),
+            Session {
+                id: "a21d18db-a276-40bc-8337-77dcd02df53e".to_owned(),
+                obtained_at_ms: 1699110415000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_session_id_with_account_id() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom
+                .load_session_id_with_account_id(
+                    "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8",
+                    "pass",
+                    "app"
+                )
+                .unwrap(),
+            "a21d18db-a276-40bc-8337-77dcd02df53e"
+        );
+    }
+
+    #[test]
+    fn test_load_session_id_with_stale_account_id() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            500,
+            r#"{"Code":"InvalidArgument","Message":"Invalid account id. Parameter name: accountId"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.load_session_id_with_account_id(
+                "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8",
+                "pass",
+                "app"
+            ),
+            Err(ClientError::DexcomError(
+                DexcomError::InvalidAccountId,
+                Some(500)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_login_progress() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let mut stages = Vec::new();
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom
+            .load_session_id_with_progress("user", "pass", "app", |stage| stages.push(stage))
+            .unwrap();
+
+        assert_eq!(
+            stages,
+            [LoginStage::Authenticating, LoginStage::FetchingSession]
+        );
+    }
+
+    #[test]
+    fn test_connection_errors_are_retried() {
+        let mut client = mock_client();
+        let mut attempts = 0;
+        client
+            .expect_post_request()
+            .with(
+                eq(url::DEXCOM_AUTHENTICATE_ENDPOINT),
+                always(),
+                always(),
+                always(),
+            )
+            .times(3)
+            .returning(move |_, _, _, mut buf| {
+                attempts += 1;
+                if attempts < 3 {
+                    return Err(TestError);
+                }
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_max_connection_retries(2);
+        assert_eq!(
+            dexcom.load_account_id("user", "pass", "app").unwrap(),
+            "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8"
+        );
+    }
+
+    #[test]
+    fn test_dexcom_errors_are_not_retried() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf.write(br#"{"Code":"AccountPasswordInvalid"}"#).unwrap(),
+                    status: 500,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_max_connection_retries(2);
+        assert!(matches!(
+            dexcom.load_account_id("user", "pass", "app"),
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(500)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_endpoints_follow_region() {
+        let mut client = mock_client();
+        let mut dexcom = Dexcom::new(&mut client);
+
+        dexcom.set_region(Region::Ous);
+        let endpoints = dexcom.endpoints();
+        assert_eq!(
+            endpoints.authenticate,
+            url::ous::DEXCOM_AUTHENTICATE_ENDPOINT
+        );
+        assert_eq!(endpoints.login, url::ous::DEXCOM_LOGIN_ID_ENDPOINT);
+        assert_eq!(
+            endpoints.glucose_readings,
+            url::ous::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+        );
+        assert_eq!(endpoints.data_range, url::ous::DEXCOM_DATA_RANGE_ENDPOINT);
+
+        dexcom.set_region(Region::Us);
+        assert_eq!(dexcom.endpoints(), Region::Us.endpoints());
+        assert!(dexcom.endpoints().login.starts_with("https://share2."));
+    }
+
+    #[test]
+    fn test_default_window_is_sent() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body == br#"{"sessionId":"s","minutes":15,"maxCount":3,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let body = format!("[{},{}]", reading_json(150, 1699110415000), reading_json(160, 1699110715000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_default_window(15, 3);
+        assert_eq!(dexcom.get_current_glucose_reading("s").unwrap().value, 160);
+    }
+
+    #[test]
+    fn test_default_window_applies_to_raw_and_conditional_reads() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body == br#"{"sessionId":"s","minutes":15,"maxCount":3,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#
+            })
+            .times(2)
+            .returning(|_, _, _, mut buf| {
+                let body = format!("[{}]", reading_json(150, 1699110415000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_default_window(15, 3);
+
+        assert_eq!(
+            dexcom.get_current_glucose_reading_raw("s").unwrap().status,
+            200
+        );
+        let last = GlucosReading::with_wall_time(140, Trend::Flat, 1699110115000);
+        assert_eq!(
+            dexcom
+                .get_current_glucose_reading_if_modified("s", &last)
+                .unwrap()
+                .map(|reading| reading.value),
+            Some(150)
+        );
+    }
+
+    #[test]
+    fn test_get_glucose_readings_as() {
+        #[derive(Deserialize)]
+        struct RealtimeReading {
+            #[serde(rename = "DT")]
+            display_time: String,
+            #[serde(rename = "Value")]
+            value: i32,
+            #[serde(rename = "RealtimeValue")]
+            realtime_value: Option<i32>,
+        }
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            r#"[{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153,"RealtimeValue":151,"Trend":"Flat"}]"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let readings: Vec<RealtimeReading> = dexcom.get_glucose_readings_as("s", 10, 1).unwrap();
+
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].display_time, "Date(1699110415000+0900)");
+        assert_eq!(readings[0].value, 153);
+        assert_eq!(readings[0].realtime_value, Some(151));
+    }
+
+    #[test]
+    fn test_default_window_returns_old_reading() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body == br#"{"sessionId":"s","minutes":1440,"maxCount":1,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let body = format!("[{}]", reading_json(150, 1699110415000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let reading = dexcom.get_current_glucose_reading("s").unwrap();
+        assert_eq!(reading.value, 150);
+        assert_eq!(reading.minutes_ago(1699110415000 + 30 * 60_000), 30);
+    }
+
+    #[test]
+    fn test_bare_and_multi_element_reading_responses() {
+        let bodies = [
+            reading_json(150, 1699110415000),
+            format!(
+                "[{},{}]",
+                reading_json(150, 1699110415000),
+                reading_json(140, 1699110115000)
+            ),
+        ];
+
+        for body in bodies {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                200,
+                body.as_str(),
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            let reading = dexcom.get_current_glucose_reading("s").unwrap();
+            assert_eq!(
+                (reading.value, reading.wall_time),
+                (150, 1699110415000),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_reading_state() {
+        let cases: [(u16, String, ReadingState); 3] = [
+            (
+                200,
+                format!("[{}]", reading_json(150, 1699110415000)),
+                ReadingState::Available(GlucosReading {
+                    value: 150,
+                    trend: Trend::Flat,
+                    wall_time: 1699110415000,
+                    system_time: 1699110415000,
+                    display_time: 1699110415000,
+                    source: Source::DexcomShare,
+                }),
+            ),
+            (200, "[]".to_owned(), ReadingState::WarmingUp),
+            (
+                500,
+                r#"{"Code":"NoActiveSensor","Message":"No active sensor session"}"#.to_owned(),
+                ReadingState::NoSensor,
+            ),
+        ];
+
+        for (status, body, state) in cases {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                status,
+                body,
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            assert_eq!(dexcom.get_reading_state("").unwrap(), state);
+        }
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            500,
+            r#"{"Code":"SessionNotValid"}"#,
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.get_reading_state(""),
+            Err(ClientError::DexcomError(
+                DexcomError::SessionInvalid,
+                Some(500)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_content_type_override() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|_, headers, _, _| {
+                headers.contains(&("Content-Type", "application/json; charset=utf-8"))
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_content_type("application/json; charset=utf-8");
+        dexcom.load_account_id("user", "pass", "app").unwrap();
+    }
+
+    #[test]
+    fn test_500_responses() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            500,
+            r#"{"Code":"SessionNotValid","Message":"Session not active or timed out"}"#,
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.get_current_glucose_reading("s"),
+            Err(ClientError::DexcomError(
+                DexcomError::SessionInvalid,
+                Some(500)
+            ))
+        ));
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            500,
+            "<html><body>Internal Server Error</body></html>",
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+        let error = dexcom.get_current_glucose_reading("s").unwrap_err();
+        assert!(matches!(error, ClientError::HttpStatus(500)));
+        assert_eq!(error.status(), Some(500));
+        assert_eq!(
+            error.connection_status(),
+            ConnectionStatus::ServiceUnavailable
+        );
+    }
+
+    #[test]
+    fn test_extra_headers() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|_, headers, _, _| {
+                headers
+                    == [
+                        ("Content-Type", "application/json"),
+                        ("User-Agent", DEFAULT_USER_AGENT),
+                        ("Authorization", "Bearer token"),
+                        ("X-Api-Key", "key"),
+                    ]
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.add_header("Authorization", "Bearer token");
+        dexcom.add_header("X-Api-Key", "key");
+        dexcom.load_account_id("user", "pass", "app").unwrap();
+    }
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!("[{}]", reading_json(153, 1699110415000)),
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_request_buffer(Vec::with_capacity(256));
+        dexcom.set_response_buffer(Vec::with_capacity(DEFAULT_BUFFER_SIZE));
+
+        dexcom.get_current_glucose_reading("s").unwrap();
+        let buffers = |dexcom: &Dexcom<MockClient>| {
+            (
+                dexcom.request_buf.as_ptr(),
+                dexcom.request_buf.capacity(),
+                dexcom.response_buf.as_ptr(),
+                dexcom.response_buf.capacity(),
+            )
+        };
+        let first = buffers(&dexcom);
+
+        for _ in 0..3 {
+            dexcom.get_current_glucose_reading("s").unwrap();
+            assert_eq!(buffers(&dexcom), first);
+        }
+        assert_eq!(first.1, 256);
+        assert_eq!(first.3, DEFAULT_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_session_hooks_wrap_login() {
+        let mut client = MockClient::new();
+        let mut sequence = mockall::Sequence::new();
+
+        client
+            .expect_begin_session()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| ());
+        client
+            .expect_post_request()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|uri, _, _, mut buf| {
+                let body = if uri == url::DEXCOM_AUTHENTICATE_ENDPOINT {
+                    "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\""
+                } else {
+                    "\"a21d18db-a276-40bc-8337-77dcd02df53e\""
+                };
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+        client
+            .expect_end_session()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| ());
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.load_session_id("user", "pass", "app").unwrap();
+    }
+
+    #[test]
+    fn test_empty_login_fields_are_rejected() {
+        let cases = [
+            (("", "pass", "app"), DexcomError::InvalidUsername),
+            (("user", "", "app"), DexcomError::InvalidPassword),
+            (("user", "pass", ""), DexcomError::InvalidApplicationId),
+        ];
+
+        for ((account_name, password, application_id), expected) in cases {
+            let mut client = MockClient::new();
+            client.expect_post_request().never();
+
+            let mut dexcom = Dexcom::new(&mut client);
+            match dexcom.load_account_id(account_name, password, application_id) {
+                Err(error @ ClientError::DexcomError(_, _)) => {
+                    assert_eq!(error.status(), None);
+                    assert!(matches!(error, ClientError::DexcomError(e, None) if e == expected));
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        let mut client = MockClient::new();
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.load_session_id_with_account_id("", "pass", "app"),
+            Err(ClientError::DexcomError(
+                DexcomError::InvalidAccountId,
+                None
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_verify_credentials_invalid_password() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"{"Code":"AccountPasswordInvalid"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.verify_credentials("user", "pass", "app"),
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(500)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_id_response() {
+        for (body, expected) in [
+            ("\"\"", DexcomError::MalformedId(String::new())),
+            (
+                "\"not-a-uuid\"",
+                DexcomError::MalformedId("not-a-uuid".to_owned()),
+            ),
+            (
+                "\"00000000-0000-0000-0000-000000000000\"",
+                DexcomError::AccountPasswordInvalid,
+            ),
+        ] {
+            let mut client = mock_client();
+            expect_response(&mut client, url::DEXCOM_AUTHENTICATE_ENDPOINT, 200, body);
+
+            let mut dexcom = Dexcom::new(&mut client);
+            match dexcom.verify_credentials("user", "pass", "app") {
+                Err(ClientError::DexcomError(error, Some(200))) => assert_eq!(error, expected),
+                _ => panic!("expected {:?} for {}", expected, body),
+            }
+        }
+    }
+
+    #[test]
+    fn test_maintenance_response() {
+        for (body, expected) in [
+            (
+                r#"{"Code":"ServiceUnavailable","Message":"Dexcom Share is undergoing scheduled Maintenance. Please try again later."}"#,
+                DexcomError::UnderMaintenance,
+            ),
+            (
+                "<html><body>Down for maintenance</body></html>",
+                DexcomError::UnderMaintenance,
+            ),
+            (
+                "<html><body>Service Unavailable</body></html>",
+                DexcomError::ServerUnavailable,
+            ),
+        ] {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                503,
+                body,
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            match dexcom.get_current_glucose_reading("") {
+                Err(ClientError::DexcomError(error, Some(503))) => assert_eq!(error, expected),
+                _ => panic!("expected {:?} for {}", expected, body),
+            }
+        }
+    }
+
+    #[test]
+    fn test_preflight_headers() {
+        let mut client = mock_client();
+        for (endpoint, body) in [
+            (
+                url::DEXCOM_AUTHENTICATE_ENDPOINT,
+                "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+            ),
+            (
+                url::DEXCOM_LOGIN_ID_ENDPOINT,
+                "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+            ),
+        ] {
+            client
+                .expect_post_request()
+                .withf(move |uri, headers, _, _| {
+                    uri == endpoint && headers.contains(&("Proxy-Authorization", "Bearer token-1"))
+                })
+                .times(1)
+                .returning(move |_, _, _, mut buf| {
+                    Ok(HttpResponse {
+                        body_len: buf.write(body.as_bytes()).unwrap(),
+                        status: 200,
+                        location: None,
+                    })
+                });
+        }
+
+        let runs = std::cell::Cell::new(0);
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_preflight(|| {
+            runs.set(runs.get() + 1);
+            Ok(vec![(
+                "Proxy-Authorization".to_owned(),
+                format!("Bearer token-{}", runs.get()),
+            )])
+        });
+
+        dexcom.load_session_id("user", "pass", "app").unwrap();
+        drop(dexcom);
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[test]
+    fn test_preflight_runs_for_every_login() {
+        let mut client = mock_client();
+        for (endpoint, token, body) in [
+            (
+                url::DEXCOM_AUTHENTICATE_ENDPOINT,
+                "Bearer token-1",
+                "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+            ),
+            (
+                url::DEXCOM_LOGIN_ID_ENDPOINT,
+                "Bearer token-2",
+                "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+            ),
+            (
+                url::DEXCOM_AUTHENTICATE_ENDPOINT,
+                "Bearer token-3",
+                "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+            ),
+        ] {
+            client
+                .expect_post_request()
+                .withf(move |uri, headers, _, _| {
+                    uri == endpoint && headers.contains(&("Proxy-Authorization", token))
+                })
+                .times(1)
+                .returning(move |_, _, _, mut buf| {
+                    Ok(HttpResponse {
+                        body_len: buf.write(body.as_bytes()).unwrap(),
+                        status: 200,
+                        location: None,
+                    })
+                });
+        }
+
+        let runs = std::cell::Cell::new(0);
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_preflight(|| {
+            runs.set(runs.get() + 1);
+            Ok(vec![(
+                "Proxy-Authorization".to_owned(),
+                format!("Bearer token-{}", runs.get()),
+            )])
+        });
+
+        let account_id = dexcom.load_account_id("user", "pass", "app").unwrap();
+        dexcom
+            .load_session_id_with_account_id(&account_id, "pass", "app")
+            .unwrap();
+        let region = dexcom.region();
+        assert_eq!(dexcom.detect_region("user", "pass", "app"), Ok(region));
+        drop(dexcom);
+        assert_eq!(runs.get(), 3);
+    }
+
+    #[test]
+    fn test_preflight_failure() {
+        let mut client = mock_client();
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_preflight(|| Err("proxy unreachable".to_owned()));
+
+        assert_eq!(
+            dexcom.load_session_id("user", "pass", "app"),
+            Err(ClientError::Preflight("proxy unreachable".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_success_status_policy() {
+        let mut calls = 0;
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .times(2)
+            .returning(move |_, _, _, mut buf| {
+                calls += 1;
+                let (status, body) = match calls {
+                    1 => (204, String::new()),
+                    _ => (200, format!("[{}]", reading_json(153, 1699110415000))),
+                };
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_success_status(|status| status != 204 && (200..=299).contains(&status));
+
+        assert_eq!(
+            dexcom
+                .get_current_glucose_reading("a21d18db-a276-40bc-8337-77dcd02df53e")
+                .unwrap_err(),
+            ClientError::HttpStatus(204)
+        );
+        assert_eq!(
+            dexcom
+                .get_current_glucose_reading("a21d18db-a276-40bc-8337-77dcd02df53e")
+                .unwrap()
+                .value,
+            153
+        );
+    }
+
+    #[test]
+    fn test_redirect() {
+        let mut client = MockClient::new();
+        client
+            .expect_post_request()
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(HttpResponse {
+                    body_len: 0,
+                    status: 302,
+                    location: Some("https://shareous1.dexcom.com/".to_owned()),
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let error = dexcom
+            .get_current_glucose_reading("a21d18db-a276-40bc-8337-77dcd02df53e")
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            ClientError::Redirect("https://shareous1.dexcom.com/".to_owned())
+        );
+        assert_eq!(error.connection_status(), ConnectionStatus::ProtocolError);
+    }
+
+    #[test]
+    fn test_error_status() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            403,
+            r#"{"Code":"SessionNotValid"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let error = dexcom.get_current_glucose_reading("").unwrap_err();
+
+        assert_eq!(error.status(), Some(403));
+        assert!(matches!(
+            error,
+            ClientError::DexcomError(DexcomError::SessionInvalid, Some(403))
+        ));
+    }
+
+    #[test]
+    fn test_auth_retry_backoff() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"{"Code":"SSO_AuthenticateMaxAttemptsExceeed"}"#,
+        );
+
+        let mut delays = Vec::new();
+        {
+            let mut dexcom = Dexcom::new(&mut client);
+            dexcom.set_auth_retry(RetryPolicy::new(Duration::from_secs(1), 3), |delay| {
+                delays.push(delay)
+            });
+
+            assert!(matches!(
+                dexcom.load_session_id("user", "pass", "app"),
+                Err(ClientError::DexcomError(
+                    DexcomError::AuthenticateMaxAttempsExceed,
+                    _
+                ))
+            ));
+        }
+
+        assert_eq!(delays, [1, 2, 4].map(Duration::from_secs).to_vec());
+    }
+
+    #[test]
+    fn test_get_glucose_readings_sorted() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!(
+                "[{},{},{},{}]",
+                reading_json(140, 1699110115000),
+                reading_json(160, 1699110715000),
+                reading_json(150, 1699110415000),
+                reading_json(151, 1699110415000)
+            ),
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let readings = dexcom.get_glucose_readings("", 30, 4).unwrap();
+
+        assert_eq!(
+            readings.iter().map(|r| r.value).collect::<Vec<_>>(),
+            [160, 150, 151, 140]
+        );
+    }
+
+    #[test]
+    fn test_lenient_readings() {
+        const BODY: &str = r#"[
+            {"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":160,"Trend":"Flat"},
+            {"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":"high","Trend":"Flat"},
+            {"WT":"Date(1699110115000)","ST":"Date(1699110115000)","DT":"Date(1699110115000)","Value":140,"Trend":"Flat"}
+        ]"#;
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            BODY,
+        );
+        let mut dexcom = Dexcom::new(&mut client);
+
+        assert!(matches!(
+            dexcom.get_glucose_readings("", 15, 3),
+            Err(ClientError::JSONError(_))
+        ));
+
+        dexcom.set_lenient_readings(true);
+        let readings = dexcom.get_glucose_readings("", 15, 3).unwrap();
+        assert_eq!(
+            readings.iter().map(|r| r.value).collect::<Vec<_>>(),
+            [160, 140]
+        );
+        assert_eq!(dexcom.skipped_readings(), 1);
+    }
+
+    #[test]
+    fn test_reading_transforms() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            r#"[
+                {"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":152,"Trend":"None"},
+                {"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":400,"Trend":"RateOutOfRange"},
+                {"WT":"Date(1699110115000)","ST":"Date(1699110115000)","DT":"Date(1699110115000)","Value":140,"Trend":"FortyFiveUp"}
+            ]"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.add_transform(transform::RejectClamped);
+        dexcom.add_transform(transform::FillTrend(Trend::Flat));
+
+        let readings = dexcom.get_glucose_readings("", 15, 3).unwrap();
+        assert_eq!(
+            readings
+                .iter()
+                .map(|r| (r.value, r.trend))
+                .collect::<Vec<_>>(),
+            [(152, Trend::Flat), (140, Trend::FortyFiveUp)]
+        );
+    }
+
+    #[test]
+    fn test_get_glucose_readings_ordered() {
+        let body = format!(
+            "[{},{},{}]",
+            reading_json(140, 1699110115000),
+            reading_json(160, 1699110715000),
+            reading_json(150, 1699110415000)
+        );
+
+        for (order, expected) in [
+            (ReadingOrder::Ascending, [140, 150, 160]),
+            (ReadingOrder::Descending, [160, 150, 140]),
+        ] {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                200,
+                body.as_str(),
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            let readings = dexcom
+                .get_glucose_readings_ordered("", 30, 3, order)
+                .unwrap();
+
+            assert_eq!(
+                readings.iter().map(|r| r.value).collect::<Vec<_>>(),
+                expected,
+                "{:?}",
+                order
+            );
+        }
+    }
+
+    #[test]
+    fn test_huge_max_count_caps_response_buffer() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .times(2)
+            .returning(|_, _, _, mut buf| {
+                assert_eq!(buf.len(), MAX_COUNT as usize * MAX_READING_JSON_LEN);
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(format!("[{}]", reading_json(150, 1699110415000)).as_bytes())
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let readings = dexcom.get_glucose_readings("", 1440, u32::MAX).unwrap();
+        assert_eq!(readings.len(), 1);
+
+        dexcom.set_default_window(1440, u32::MAX);
+        assert_eq!(dexcom.get_current_glucose_reading("").unwrap().value, 150);
+    }
+
+    #[test]
+    fn test_get_glucose_readings_into_small_buffer() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!(
+                "[{},{},{}]",
+                reading_json(160, 1699110715000),
+                reading_json(150, 1699110415000),
+                reading_json(140, 1699110115000)
+            ),
+        );
+
+        let mut out = [GlucosReading::synthetic(0, Trend::None, 0); 2];
+        let mut dexcom = Dexcom::new(&mut client);
+
+        assert_eq!(
+            dexcom
+                .get_glucose_readings_into("", 30, 3, &mut out)
+                .unwrap(),
+            2
+        );
+        assert_eq!(out.map(|r| r.value), [160, 150]);
+
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!(
+                "[{},{},{}]",
+                reading_json(140, 1699110115000),
+                reading_json(150, 1699110415000),
+                reading_json(160, 1699110715000)
+            ),
+        );
+
+        let mut out = [GlucosReading::synthetic(0, Trend::None, 0); 2];
+        let mut dexcom = Dexcom::new(&mut client);
+        assert_eq!(
+            dexcom
+                .get_glucose_readings_into("", 30, 3, &mut out)
+                .unwrap(),
+            2
+        );
+        assert_eq!(out.map(|r| r.value), [160, 150]);
+        assert_eq!(
+            dexcom
+                .get_glucose_readings_into("", 30, 3, &mut [])
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_get_readings_since() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body == br#"{"sessionId":"s","minutes":16,"maxCount":288,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                let body = format!("[{},{},{},{}]", reading_json(160, 1699110715000), reading_json(160, 1699110715000), reading_json(150, 1699110415000), reading_json(140, 1699110115000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let since = 1699110415000;
+        let now = since + 15 * 60_000 + 1;
+        let mut dexcom = Dexcom::new(&mut client);
+        let readings = dexcom.get_readings_since("s", since, now).unwrap();
+
+        assert_eq!(
+            readings.iter().map(|r| r.value).collect::<Vec<_>>(),
+            [160, 150]
+        );
+    }
+
+    #[test]
+    fn test_get_readings_since_caps_window() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|_, _, body, _| body == br#"{"sessionId":"s","minutes":1440,"maxCount":288,"applicationId":"d89443d2-327c-4a6f-89e5-496bbb0317db"}"#)
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf.write(b"[]").unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let now = 1699110415000;
+        assert!(dexcom
+            .get_readings_since("s", now - 3 * 86_400_000, now)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_glucose_extremes() {
+        let cases: [(String, Option<(i32, i32)>); 3] = [
+            ("[]".to_owned(), None),
+            (
+                format!("[{}]", reading_json(150, 1699110415000)),
+                Some((150, 150)),
+            ),
+            (
+                format!(
+                    "[{},{},{}]",
+                    reading_json(160, 1699110715000),
+                    reading_json(95, 1699110415000),
+                    reading_json(140, 1699110115000)
+                ),
+                Some((95, 160)),
+            ),
+        ];
+
+        for (body, expected) in cases {
+            let mut client = mock_client();
+            expect_response(
+                &mut client,
+                url::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+                200,
+                body,
+            );
+
+            let mut dexcom = Dexcom::new(&mut client);
+            let extremes = dexcom.get_glucose_extremes("", 1440).unwrap();
+
+            assert_eq!(extremes.map(|(min, max)| (min.value, max.value)), expected);
+        }
+    }
+
+    #[test]
+    fn test_load_session_ids() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_AUTHENTICATE_ENDPOINT
+                    && std::str::from_utf8(body).unwrap().contains("\"bob\"")
+            })
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf.write(br#"{"Code":"AccountPasswordInvalid"}"#).unwrap(),
+                    status: 500,
+                    location: None,
+                })
+            });
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+
+        let accounts = [
+            Credentials::new("alice", "pass", "app"),
+            Credentials::new("bob", "wrong", "app"),
+            Credentials::new("carol", "pass", "app"),
+        ];
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let results = dexcom.load_session_ids(&accounts);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0.account_name, "alice");
+        assert!(results[0].1.is_ok());
+        assert!(matches!(
+            results[1].1,
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(500)
+            ))
+        ));
+        assert!(results[2].1.is_ok());
+    }
+
+    #[test]
+    fn test_fetch_from_regions_merges_readings() {
+        let mut client = mock_client();
+        for uri in [
+            url::us::DEXCOM_AUTHENTICATE_ENDPOINT,
+            url::ous::DEXCOM_AUTHENTICATE_ENDPOINT,
+        ] {
+            expect_response(
+                &mut client,
+                uri,
+                200,
+                "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+            );
+        }
+        for uri in [
+            url::us::DEXCOM_LOGIN_ID_ENDPOINT,
+            url::ous::DEXCOM_LOGIN_ID_ENDPOINT,
+        ] {
+            expect_response(
+                &mut client,
+                uri,
+                200,
+                "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+            );
+        }
+        expect_response(
+            &mut client,
+            url::us::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!(
+                "[{},{}]",
+                reading_json(153, 1699110415000),
+                reading_json(150, 1699110115000)
+            ),
+        );
+        expect_response(
+            &mut client,
+            url::ous::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            r#"[{"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000+0900)","Value":158,"Trend":"FortyFiveUp"},{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153,"Trend":"Flat"}]"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let readings = dexcom
+            .fetch_from_regions(
+                &Credentials::new("user", "pass", "app"),
+                &[Region::Us, Region::Ous],
+            )
+            .unwrap();
+
+        let values: Vec<_> = readings.iter().map(|r| r.value).collect();
+        assert_eq!(values, [158, 153, 150]);
+        assert_eq!(dexcom.region(), Region::default());
+    }
+
+    #[test]
+    fn test_fetch_from_regions_tolerates_failed_region() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::us::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"{"Code":"AccountPasswordInvalid"}"#,
+        );
+        expect_response(
+            &mut client,
+            url::ous::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::ous::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
+        expect_response(
+            &mut client,
+            url::ous::DEXCOM_GLUCOSE_READINGS_ENDPOINT,
+            200,
+            format!(
+                "[{},{}]",
+                reading_json(150, 1699110415000),
+                reading_json(160, 1699110715000)
+            ),
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let region = dexcom.region();
+        let readings = dexcom
+            .fetch_from_regions(
+                &Credentials::new("user", "pass", "app"),
+                &[Region::Us, Region::Ous],
+            )
+            .unwrap();
+
+        assert_eq!(
+            readings.iter().map(|r| r.value).collect::<Vec<_>>(),
+            [160, 150]
+        );
+        assert_eq!(dexcom.region(), region);
+
+        // Only when every region fails is the error returned.
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::us::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"{"Code":"AccountPasswordInvalid"}"#,
+        );
+        expect_response(
+            &mut client,
+            url::ous::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"{"Code":"AccountPasswordInvalid"}"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        let result = dexcom.fetch_from_regions(
+            &Credentials::new("user", "pass", "app"),
+            &[Region::Us, Region::Ous],
+        );
+
+        assert!(matches!(
+            result,
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                _
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_user_agent() {
+        let mut client = mock_client();
 
-#[cfg(not(feature = "ous"))]
-mod url {
-    pub(crate) const DEXCOM_GLUCOSE_READINGS_ENDPOINT: &str = 
-        "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherLatestGlucoseValues";
-    pub(crate) const DEXCOM_LOGIN_ID_ENDPOINT: &str =
-        "https://share2.dexcom.com/ShareWebServices/Services/General/LoginPublisherAccountById";
-    pub(crate) const DEXCOM_AUTHENTICATE_ENDPOINT: &str =
-        "https://share2.dexcom.com/ShareWebServices/Services/General/AuthenticatePublisherAccount";    
-}
+        client
+            .expect_post_request()
+            .withf(|_, headers, _, _| headers.contains(&("User-Agent", DEFAULT_USER_AGENT)))
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
 
-#[cfg(test)]
-mod tests {
-    use std::io::Write;
+        client
+            .expect_post_request()
+            .withf(|_, headers, _, _| headers.contains(&("User-Agent", "Dexcom Share/3.0.2.11")))
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
 
-    use mockall::predicate::*;
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.get_account_id("user", "pass", "app").unwrap();
 
-    use super::*;
-    use super::client::*;
+        dexcom.set_user_agent("Dexcom Share/3.0.2.11");
+        dexcom.get_session_id("user", "pass", "app").unwrap();
+    }
 
     #[test]
-    fn test_get_current_glucose_reading() {
-        let mut client = MockClient::new();
+    fn test_request_id() {
+        let mut client = mock_client();
 
         client
             .expect_post_request()
-            .with(
-                eq(url::DEXCOM_AUTHENTICATE_ENDPOINT),
-                always(),
-                always(),
-                always(),
-            )
+            .withf(|_, headers, _, _| headers.contains(&("X-Request-ID", "trace-42")))
+            .times(1)
             .returning(|_, _, _, mut buf| {
-                let size = buf
-                    .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
-                    .unwrap();
-                Ok((size, 200u16))
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(b"\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"")
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
             });
 
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.set_request_id("trace-42");
+        dexcom.get_account_id("user", "pass", "app").unwrap();
+    }
+
+    #[test]
+    fn test_request_field_order() {
+        assert_eq!(
+            serde_json::to_string(&GetLatestGlucoseValuesRequest {
+                session_id: "s",
+                minutes: 10,
+                max_count: 1,
+                application_id: "i",
+            })
+            .unwrap(),
+            r#"{"sessionId":"s","minutes":10,"maxCount":1,"applicationId":"i"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&GetAccountIdRequest {
+                account_name: "a",
+                password: "p",
+                application_id: "i",
+            })
+            .unwrap(),
+            r#"{"accountName":"a","password":"p","applicationId":"i"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&GetSessionIdRequest {
+                account_id: "a",
+                password: "p",
+                application_id: "i",
+            })
+            .unwrap(),
+            r#"{"accountId":"a","password":"p","applicationId":"i"}"#
+        );
+    }
+
+    #[test]
+    fn test_glucose_request_includes_application_id() {
+        let mut client = mock_client();
         client
             .expect_post_request()
-            .with(
-                eq(url::DEXCOM_LOGIN_ID_ENDPOINT),
-                always(),
-                always(),
-                always(),
-            )
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body
+                        .ends_with(br#","applicationId":"00000000-0000-0000-0000-00000000000a"}"#)
+            })
+            .times(1)
             .returning(|_, _, _, mut buf| {
-                let size = buf
-                    .write(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"")
-                    .unwrap();
-                Ok((size, 200u16))
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(format!("[{}]", reading_json(153, 1699110415000)).as_bytes())
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
             });
 
+        let mut dexcom = builder::DexcomBuilder::new(&mut client)
+            .application_id("00000000-0000-0000-0000-00000000000a")
+            .build()
+            .unwrap();
+        assert_eq!(dexcom.get_current_glucose_reading("s").unwrap().value, 153);
+    }
+
+    #[test]
+    fn test_login_application_id_is_used_for_reads() {
+        let application_id = "00000000-0000-0000-0000-00000000000b";
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+        expect_response(
+            &mut client,
+            url::DEXCOM_LOGIN_ID_ENDPOINT,
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        );
         client
             .expect_post_request()
-            .with(
-                eq(url::DEXCOM_GLUCOSE_READINGS_ENDPOINT),
-                always(),
-                always(),
-                always(),
-            )
+            .withf(|uri, _, body, _| {
+                uri == url::DEXCOM_GLUCOSE_READINGS_ENDPOINT
+                    && body
+                        .ends_with(br#","applicationId":"00000000-0000-0000-0000-00000000000b"}"#)
+            })
+            .times(1)
             .returning(|_, _, _, mut buf| {
-                let size = buf.write(r#"[{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153,"Trend":"Flat"}]"#.as_bytes()).unwrap();
-                Ok((size, 200u16))
+                Ok(HttpResponse {
+                    body_len: buf
+                        .write(format!("[{}]", reading_json(153, 1699110415000)).as_bytes())
+                        .unwrap(),
+                    status: 200,
+                    location: None,
+                })
             });
 
         let mut dexcom = Dexcom::new(&mut client);
+        let session_id = dexcom
+            .load_session_id("user", "pass", application_id)
+            .unwrap();
+        assert_eq!(dexcom.application_id(), application_id);
+        assert_eq!(
+            dexcom
+                .get_current_glucose_reading(&session_id)
+                .unwrap()
+                .value,
+            153
+        );
+    }
 
-        let session_id = dexcom.load_session_id("", "", "").unwrap();
-        assert_eq!(session_id, "a21d18db-a276-40bc-8337-77dcd02df53e");
+    #[test]
+    fn test_request_from_owned_strings() {
+        let credentials = Credentials::new("a", "p", "i");
+        let request = GetAccountIdRequest {
+            account_name: credentials.account_name.clone(),
+            password: credentials.password.clone(),
+            application_id: credentials.application_id.clone(),
+        };
+        drop(credentials);
 
-        let glucose = dexcom.get_current_glucose_reading(&session_id);
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"accountName":"a","password":"p","applicationId":"i"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&GetLatestGlucoseValuesRequest {
+                session_id: String::from("s"),
+                minutes: 10,
+                max_count: 1,
+                application_id: String::from("i"),
+            })
+            .unwrap(),
+            r#"{"sessionId":"s","minutes":10,"maxCount":1,"applicationId":"i"}"#
+        );
+    }
+
+    #[test]
+    fn test_error_classification() {
+        let cases = [
+            (DexcomError::AccountPasswordInvalid, true, false),
+            (DexcomError::AuthenticateMaxAttempsExceed, true, false),
+            (DexcomError::AccountLocked, true, false),
+            (DexcomError::SessionNotFound, false, true),
+            (DexcomError::SessionInvalid, false, true),
+            (DexcomError::InvalidUsername, true, false),
+            (DexcomError::InvalidPassword, true, false),
+            (DexcomError::InvalidApplicationId, true, false),
+            (DexcomError::InvalidAccountId, true, false),
+            (DexcomError::InvalidSessionId, false, true),
+            (DexcomError::InvalidUnknown, false, false),
+            (DexcomError::MalformedId(String::new()), false, false),
+            (DexcomError::UnderMaintenance, false, false),
+            (DexcomError::ServerUnavailable, false, false),
+            (
+                DexcomError::Unknown {
+                    code: None,
+                    message: None,
+                },
+                false,
+                false,
+            ),
+        ];
+
+        for (error, auth, session) in cases {
+            assert_eq!(error.is_auth_error(), auth, "{:?}", error);
+            assert_eq!(error.is_session_error(), session, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_connection_status() {
+        type Error = ClientError<TestError>;
 
-        assert!(glucose.is_ok());
         assert_eq!(
-            glucose.unwrap(),
-            GlucosReading {
-                trend: Trend::Flat,
-                value: 153,
-            }
-        )
+            Error::ConnectionError(TestError).connection_status(),
+            ConnectionStatus::Offline
+        );
+        assert_eq!(
+            Error::DexcomError(DexcomError::AccountPasswordInvalid, Some(500)).connection_status(),
+            ConnectionStatus::AuthFailed
+        );
+        assert_eq!(
+            Error::DexcomError(DexcomError::SessionNotFound, Some(500)).connection_status(),
+            ConnectionStatus::SessionExpired
+        );
+        assert_eq!(
+            Error::DexcomError(DexcomError::UnderMaintenance, Some(503)).connection_status(),
+            ConnectionStatus::ServiceUnavailable
+        );
+        assert_eq!(
+            Error::RateLimited(Duration::from_secs(15)).connection_status(),
+            ConnectionStatus::Throttled
+        );
+        assert_eq!(
+            Error::Preflight("proxy token expired".to_owned()).connection_status(),
+            ConnectionStatus::AuthFailed
+        );
+
+        let json_error = serde_json::from_str::<GlucosReading>("{").unwrap_err();
+        assert_eq!(
+            Error::JSONError(SerdeJsonError(json_error)).connection_status(),
+            ConnectionStatus::ProtocolError
+        );
     }
 
     #[test]
@@ -344,4 +5313,580 @@ mod tests {
         let error: DexcomError = response.into();
         assert_eq!(error, DexcomError::SessionNotFound);
     }
+
+    #[test]
+    fn test_array_wrapped_error_response() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            500,
+            r#"[{"Code":"AccountPasswordInvalid","Message":"Publisher account password failed"}]"#,
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        assert!(matches!(
+            dexcom.load_account_id("user", "pass", "app"),
+            Err(ClientError::DexcomError(
+                DexcomError::AccountPasswordInvalid,
+                Some(500)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_account_locked_error_response() {
+        let message = r#"{"Code":"SSO_AuthenticateAccountLocked","Message":"Account is locked. Please try again later.","SubCode":"<OnlineException DateThrownLocal=\"2023-11-04 15:06:55\" ErrorCode=\"SSO_AuthenticateAccountLocked\" />","TypeName":"ClientFaultException"}"#;
+        let response = serde_json::from_str::<DexcomErrorResponse>(message).unwrap();
+        let error: DexcomError = response.into();
+
+        assert_eq!(error, DexcomError::AccountLocked);
+    }
+
+    #[test]
+    fn test_unknown_error_response_preserves_payload() {
+        let message = r#"{"Code":"SomethingNew","Message":"Brand new failure"}"#;
+        let response = serde_json::from_str::<DexcomErrorResponse>(message).unwrap();
+
+        let error: DexcomError = response.into();
+        assert_eq!(
+            error,
+            DexcomError::Unknown {
+                code: Some("SomethingNew".to_owned()),
+                message: Some("Brand new failure".to_owned()),
+            }
+        );
+
+        let response = serde_json::from_str::<DexcomErrorResponse>("{}").unwrap();
+        let error: DexcomError = response.into();
+        assert_eq!(
+            error,
+            DexcomError::Unknown {
+                code: None,
+                message: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_argument_error_response() {
+        let fixtures = [
+            (
+                r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: accountName","SubCode":"<OnlineException DateThrownLocal=\"2023-11-04 15:06:55\" ErrorCode=\"InvalidArgument\" TypeString=\"Validation\" Message=\"Expected a non-empty string. Parameter name: accountName\" />","TypeName":"ArgumentException"}"#,
+                DexcomError::InvalidUsername,
+            ),
+            (
+                r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: password","SubCode":"<OnlineException DateThrownLocal=\"2023-11-04 15:06:55\" ErrorCode=\"InvalidArgument\" TypeString=\"Validation\" Message=\"Expected a non-empty string. Parameter name: password\" />","TypeName":"ArgumentException"}"#,
+                DexcomError::InvalidPassword,
+            ),
+            (
+                r#"{"Code":"InvalidArgument","Message":"Guid should contain 32 digits with 4 dashes (xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx).","SubCode":"<OnlineException DateThrownLocal=\"2023-11-04 15:06:55\" ErrorCode=\"InvalidArgument\" TypeString=\"Validation\" />","TypeName":"FormatException"}"#,
+                DexcomError::InvalidAccountId,
+            ),
+            (
+                r#"{"Code":"InvalidArgument","Message":"Something about the accountName changed"}"#,
+                DexcomError::InvalidUsername,
+            ),
+            (
+                r#"{"Code":"InvalidArgument","Message":"Unexpected"}"#,
+                DexcomError::InvalidUnknown,
+            ),
+        ];
+
+        for (message, expected) in fixtures {
+            let response = serde_json::from_str::<DexcomErrorResponse>(message).unwrap();
+            let error: DexcomError = response.into();
+            assert_eq!(error, expected, "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_scanned_error_response() {
+        let bodies = [
+            r#"{"Code":"SessionIdNotFound"}"#,
+            r#"{"Code":"SessionNotValid","Message":"Session not active or timed out"}"#,
+            r#"{ "Code" : "AccountPasswordInvalid" }"#,
+            r#"[{"Code":"SSO_AuthenticateMaxAttemptsExceeed"}]"#,
+            r#"{"Code":"SSO_AuthenticateAccountLocked","SubCode":"<OnlineException ErrorCode=\"SSO_AuthenticateAccountLocked\" />"}"#,
+            r#"{"Code":"AccountLocked"}"#,
+            r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: password","TypeName":"ArgumentException"}"#,
+            r#"{"Code":"InvalidArgument","SubCode":"<OnlineException Message=\"Value cannot be null. Parameter name: applicationId\" />"}"#,
+            r#"{"Code":"InvalidArgument","Message":"Guid should contain 32 digits","TypeName":"FormatException"}"#,
+            r#"{"Code":"SomethingNew","message":"Brand new failure"}"#,
+        ];
+
+        for body in bodies {
+            let scanned: DexcomError = scan_error_response(body.as_bytes()).unwrap().into();
+            let parsed: DexcomError = error_response_from_slice(body.as_bytes()).unwrap().into();
+            assert_eq!(scanned, parsed, "{}", body);
+        }
+
+        assert!(scan_error_response(b"<html>Bad Gateway</html>").is_none());
+        assert!(scan_error_response(br#"{"Code":null}"#).is_none());
+    }
+
+    #[test]
+    fn test_float_glucose_value() {
+        let reading = serde_json::from_str::<GlucosReading>(
+            r#"{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153.4,"Trend":"Flat"}"#,
+        )
+        .unwrap();
+        assert_eq!(reading.value, 153);
+
+        let reading = serde_json::from_str::<GlucosReading>(
+            r#"{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153.5,"Trend":"Flat"}"#,
+        )
+        .unwrap();
+        assert_eq!(reading.value, 154);
+    }
+
+    #[test]
+    fn test_string_glucose_value() {
+        let parse = |value: &str| {
+            serde_json::from_str::<GlucosReading>(&format!(
+                r#"{{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":{},"Trend":"Flat"}}"#,
+                value
+            ))
+        };
+
+        assert_eq!(parse("153").unwrap().value, 153);
+        assert_eq!(parse(r#""153""#).unwrap().value, 153);
+        assert_eq!(parse(r#"" 153.5 ""#).unwrap().value, 154);
+        assert!(parse(r#""high""#)
+            .unwrap_err()
+            .to_string()
+            .starts_with(r#"invalid glucose value "high""#));
+        assert!(parse(r#""NaN""#).is_err());
+    }
+
+    #[test]
+    fn test_glucose_reading_field_aliases() {
+        let expected = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        let fields = [
+            ("Value", "value", "153"),
+            ("Trend", "trend", "\"Flat\""),
+            ("WT", "wt", "\"Date(1699110415000)\""),
+            ("ST", "st", "\"Date(1699110415000)\""),
+            ("DT", "dt", "\"Date(1699110415000+0900)\""),
+        ];
+
+        for mask in 0..1 << fields.len() {
+            let body = fields
+                .iter()
+                .enumerate()
+                .map(|(i, (upper, lower, value))| {
+                    let name = if mask & (1 << i) == 0 { upper } else { lower };
+                    format!("\"{}\":{}", name, value)
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            let body = format!("{{{}}}", body);
+
+            let reading = serde_json::from_str::<GlucosReading>(&body).unwrap();
+            assert_eq!(reading, expected, "{}", body);
+        }
+    }
+
+    #[test]
+    fn test_debug_masks_password() {
+        let formatted = [
+            format!("{:?}", Credentials::new("user", "hunter2", "app")),
+            format!(
+                "{:?}",
+                GetAccountIdRequest {
+                    account_name: "user",
+                    password: "hunter2",
+                    application_id: "app",
+                }
+            ),
+            format!(
+                "{:?}",
+                GetSessionIdRequest {
+                    account_id: "1e913fce-5a34-4d27-a991-b6cb3a3bd3d8",
+                    password: "hunter2",
+                    application_id: "app",
+                }
+            ),
+        ];
+
+        for formatted in formatted {
+            assert!(!formatted.contains("hunter2"), "{}", formatted);
+            assert!(formatted.contains(r#"password: "***""#), "{}", formatted);
+            assert!(formatted.contains(r#""app""#), "{}", formatted);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_credentials_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut credentials = Credentials::new("user", "hunter2", "app");
+        credentials.zeroize();
+
+        assert_eq!(credentials, Credentials::new("user", "", "app"));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_request_body_is_zeroized() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            url::DEXCOM_AUTHENTICATE_ENDPOINT,
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        );
+
+        let mut dexcom = Dexcom::new(&mut client);
+        dexcom.load_account_id("user", "hunter2", "app").unwrap();
+
+        let body = &dexcom.request_buf;
+        assert!(body.is_empty());
+        assert!(body.capacity() > 0);
+        // SAFETY: the whole capacity was written by the serializer or by
+        // `zeroize`, so it is initialized.
+        let allocation = unsafe { std::slice::from_raw_parts(body.as_ptr(), body.capacity()) };
+        assert!(allocation.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn test_unrecognized_trend_keeps_reading() {
+        let reading = serde_json::from_str::<GlucosReading>(
+            r#"{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":120,"Trend":"SomethingNew"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(reading.value, 120);
+        assert_eq!(reading.trend, Trend::Unknown);
+    }
+
+    #[test]
+    fn test_minutes_ago() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        assert_eq!(reading.minutes_ago(1699110415000), 0);
+        assert_eq!(reading.minutes_ago(1699110415000 + 5 * 60_000), 5);
+        assert_eq!(reading.minutes_ago(1699110415000 + 5 * 60_000 + 59_999), 5);
+        assert_eq!(reading.minutes_ago(1699110415000 - 3 * 60_000), 0);
+    }
+
+    #[test]
+    fn test_glucose_reading_round_trip() {
+        let reading = serde_json::from_str::<GlucosReading>(
+            r#"{"WT":"Date(1699110415000)","ST":"Date(1699110416000)","DT":"Date(1699110417000+0900)","Value":153,"Trend":"FortyFiveUp"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(reading.value, 153);
+        assert_eq!(reading.trend, Trend::FortyFiveUp);
+        assert_eq!(reading.wall_time, 1699110415000);
+        assert_eq!(reading.system_time, 1699110416000);
+        assert_eq!(reading.display_time, 1699110417000);
+        assert_eq!(reading.source, Source::DexcomShare);
+
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Value":153,"Trend":"FortyFiveUp","WT":"Date(1699110415000)","ST":"Date(1699110416000)","DT":"Date(1699110417000)"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<GlucosReading>(&json).unwrap(),
+            reading
+        );
+    }
+
+    #[test]
+    fn test_synthetic_reading_source() {
+        let reading = GlucosReading::synthetic(120, Trend::Flat, 1699110415000);
+        assert_eq!(reading.source, Source::Synthetic);
+
+        let json = serde_json::to_string(&reading).unwrap();
+        assert!(json.ends_with(r#","Source":"Synthetic"}"#));
+        assert_eq!(
+            serde_json::from_str::<GlucosReading>(&json).unwrap(),
+            reading
+        );
+    }
+
+    #[test]
+    fn test_time_to_threshold_with_uncertainty() {
+        let reading = GlucosReading {
+            value: 100,
+            trend: Trend::SingleDown,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        assert_eq!(
+            reading.time_to_threshold_with_uncertainty(70),
+            Some((15, 10))
+        );
+        assert_eq!(reading.time_to_threshold_with_uncertainty(180), None);
+        assert_eq!(
+            reading.time_to_threshold_with_uncertainty(100),
+            Some((0, 0))
+        );
+
+        let flat = GlucosReading {
+            trend: Trend::Flat,
+            ..reading
+        };
+        assert_eq!(flat.time_to_threshold_with_uncertainty(70), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        assert_eq!(reading.to_string(), "153 →");
+        assert_eq!(reading.summary(GlucoseUnit::MgDl), "153 mg/dL →");
+        assert_eq!(reading.summary(GlucoseUnit::MmolL), "8.5 mmol/L →");
+
+        let low = GlucosReading {
+            value: 39,
+            trend: Trend::None,
+            ..reading
+        };
+        assert_eq!(low.to_string(), "LOW");
+        assert_eq!(low.summary(GlucoseUnit::MmolL), "LOW");
+
+        let high = GlucosReading {
+            value: 401,
+            trend: Trend::DoubleUp,
+            ..reading
+        };
+        assert_eq!(high.to_string(), "HIGH ↑↑");
+
+        let clamped_low = GlucosReading {
+            value: SENSOR_LOW,
+            ..reading
+        };
+        assert_eq!(clamped_low.to_string(), "LOW →");
+
+        let clamped_high = GlucosReading {
+            value: SENSOR_HIGH,
+            ..reading
+        };
+        assert_eq!(clamped_high.to_string(), "HIGH →");
+        assert_eq!(clamped_high.summary(GlucoseUnit::MgDl), "HIGH →");
+
+        let in_range = GlucosReading {
+            value: SENSOR_HIGH - 1,
+            ..reading
+        };
+        assert_eq!(in_range.to_string(), "399 →");
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+        let later = GlucosReading {
+            value: 155,
+            wall_time: reading.wall_time + 5 * 60_000,
+            ..reading
+        };
+
+        assert!(reading.approx_eq(&reading, 0));
+        assert!(reading.approx_eq(&later, 2));
+        assert!(later.approx_eq(&reading, 2));
+        assert!(!reading.approx_eq(&later, 1));
+        assert!(!reading.approx_eq(
+            &GlucosReading {
+                trend: Trend::FortyFiveUp,
+                ..reading
+            },
+            10
+        ));
+    }
+
+    #[test]
+    fn test_mmol_l_tenths() {
+        let tenths = |value| GlucosReading::synthetic(value, Trend::Flat, 0).mmol_l_tenths();
+
+        assert_eq!(tenths(153), 85);
+        assert_eq!(tenths(99), 55);
+        // 55.5 and 10.545 round up, 22.2 and 9.435 round down.
+        assert_eq!(tenths(100), 56);
+        assert_eq!(tenths(19), 11);
+        assert_eq!(tenths(40), 22);
+        assert_eq!(tenths(17), 9);
+        assert_eq!(tenths(9), 5);
+        assert_eq!(tenths(0), 0);
+
+        for value in SENSOR_LOW..=SENSOR_HIGH {
+            let reading = GlucosReading::synthetic(value, Trend::Flat, 0);
+            let tenths = reading.mmol_l_tenths();
+            let mmol_l = reading.value_in(GlucoseUnit::MmolL);
+            assert!(
+                (mmol_l * 10.0 - tenths as f32).abs() <= 0.5 + 1e-3,
+                "{}",
+                value
+            );
+            if !reading.is_below_range() && !reading.is_above_range() {
+                assert_eq!(
+                    reading.summary(GlucoseUnit::MmolL),
+                    format!("{}.{} mmol/L →", tenths / 10, tenths % 10)
+                );
+            }
+        }
+        assert_eq!(tenths(136), 75);
+        assert_eq!(
+            GlucosReading::synthetic(136, Trend::Flat, 0).summary(GlucoseUnit::MmolL),
+            "7.5 mmol/L →"
+        );
+    }
+
+    #[test]
+    fn test_staleness() {
+        let reading = GlucosReading::synthetic(153, Trend::Flat, 1699110415000);
+        let at = |minutes: i64| reading.staleness(reading.wall_time + minutes * 60_000);
+
+        assert_eq!(at(4), Staleness::Fresh);
+        assert_eq!(at(5), Staleness::Fresh);
+        assert_eq!(at(6), Staleness::Recent);
+        assert_eq!(at(10), Staleness::Recent);
+        assert_eq!(at(11), Staleness::Stale);
+        assert_eq!(at(20), Staleness::Stale);
+        assert_eq!(at(21), Staleness::Old);
+        assert_eq!(at(25), Staleness::Old);
+        assert_eq!(at(-3), Staleness::Fresh);
+    }
+
+    #[test]
+    fn test_signal_status() {
+        let status = |trend| GlucosReading::synthetic(153, trend, 0).signal_status();
+
+        assert_eq!(status(Trend::NotComputable), SignalStatus::NotComputable);
+        assert_eq!(status(Trend::RateOutOfRange), SignalStatus::OutOfRange);
+        assert_eq!(status(Trend::Flat), SignalStatus::Ok);
+        assert_eq!(status(Trend::DoubleDown), SignalStatus::Ok);
+        assert_eq!(status(Trend::None), SignalStatus::Ok);
+    }
+
+    #[test]
+    fn test_binary_record() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::FortyFiveDown,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        let bytes = reading.to_bytes();
+        assert_eq!(bytes, [153, 0, 5, 0x0f, 0x5e, 0x46, 0x65]);
+        assert_eq!(GlucosReading::from_bytes(&bytes), Some(reading));
+
+        let unknown = GlucosReading {
+            trend: Trend::Unknown,
+            ..reading
+        };
+        assert_eq!(
+            GlucosReading::from_bytes(&unknown.to_bytes()),
+            Some(unknown)
+        );
+
+        let mut invalid = bytes;
+        invalid[2] = 11;
+        assert_eq!(GlucosReading::from_bytes(&invalid), None);
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::Flat,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+        let same = reading;
+
+        assert_eq!(reading.state_hash(), same.state_hash());
+        assert_ne!(
+            reading.state_hash(),
+            GlucosReading {
+                value: 154,
+                ..reading
+            }
+            .state_hash()
+        );
+        assert_ne!(
+            reading.state_hash(),
+            GlucosReading {
+                trend: Trend::FortyFiveUp,
+                ..reading
+            }
+            .state_hash()
+        );
+        assert_ne!(
+            reading.state_hash(),
+            GlucosReading {
+                wall_time: reading.wall_time + 300_000,
+                ..reading
+            }
+            .state_hash()
+        );
+    }
+
+    #[test]
+    fn test_glucose_reading_as_map_key() {
+        use std::collections::HashMap;
+
+        let reading =
+            serde_json::from_str::<GlucosReading>(&reading_json(153, 1699110415000)).unwrap();
+        let copied = reading;
+
+        let mut counts = HashMap::new();
+        *counts.entry(reading).or_insert(0) += 1;
+        *counts.entry(copied).or_insert(0) += 1;
+
+        assert_eq!(counts[&reading], 2);
+        assert_eq!(copied.trend, Trend::Flat);
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn test_defmt_format() {
+        fn assert_format<T: defmt::Format>() {}
+
+        assert_format::<Trend>();
+        assert_format::<GlucosReading>();
+        assert_format::<DexcomError>();
+        assert_format::<ClientError<TestError>>();
+    }
 }
@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use crate::{timestamp, GlucosReading};
+
+/// A Nightscout `entries` record of type `sgv`.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct NightscoutEntry {
+    #[serde(rename = "type")]
+    pub entry_type: &'static str,
+    pub sgv: i32,
+    pub direction: &'static str,
+    pub date: i64,
+    #[serde(rename = "dateString")]
+    pub date_string: String,
+}
+
+impl GlucosReading {
+    pub fn to_nightscout_entry(&self) -> NightscoutEntry {
+        NightscoutEntry {
+            entry_type: "sgv",
+            sgv: self.value,
+            direction: self.trend.nightscout_direction(),
+            date: self.wall_time,
+            date_string: timestamp::to_iso8601(self.wall_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Source, Trend};
+
+    #[test]
+    fn test_to_nightscout_entry() {
+        let reading = GlucosReading {
+            value: 153,
+            trend: Trend::FortyFiveUp,
+            wall_time: 1699110415000,
+            system_time: 1699110415000,
+            display_time: 1699110415000,
+            source: Source::DexcomShare,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&reading.to_nightscout_entry()).unwrap(),
+            r#"{"type":"sgv","sgv":153,"direction":"FortyFiveUp","date":1699110415000,"dateString":"2023-11-04T15:06:55.000Z"}"#
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use crate::client::Client;
+use crate::{ClientError, Dexcom, GlucosReading, DEXCOM_READING_INTERVAL_SECS};
+
+type Result<T, C> = std::result::Result<T, ClientError<<C as Client>::Error>>;
+
+/// Polls the latest reading for a session and only yields readings whose
+/// wall time advanced past the last one yielded.
+pub struct GlucosePoller<'a, C: Client> {
+    dexcom: Dexcom<'a, C>,
+    session_id: String,
+    last_wall_time: Option<i64>,
+}
+
+impl<'a, C: Client> GlucosePoller<'a, C> {
+    pub fn new(dexcom: Dexcom<'a, C>, session_id: &str) -> Self {
+        Self {
+            dexcom,
+            session_id: session_id.to_owned(),
+            last_wall_time: None,
+        }
+    }
+
+    pub fn set_session_id(&mut self, session_id: &str) {
+        self.session_id = session_id.to_owned();
+    }
+
+    pub fn dexcom(&mut self) -> &mut Dexcom<'a, C> {
+        &mut self.dexcom
+    }
+
+    pub fn into_inner(self) -> Dexcom<'a, C> {
+        self.dexcom
+    }
+
+    /// Returns `None` when the latest reading is the one already yielded.
+    pub fn poll(&mut self) -> Result<Option<GlucosReading>, C> {
+        let reading = self.dexcom.get_current_glucose_reading(&self.session_id)?;
+
+        if matches!(self.last_wall_time, Some(last) if reading.wall_time <= last) {
+            return Ok(None);
+        }
+
+        self.last_wall_time = Some(reading.wall_time);
+        Ok(Some(reading))
+    }
+}
+
+impl<C: Client> Dexcom<'_, C> {
+    /// Fetches the latest reading, hands the outcome to `on_reading`, sleeps
+    /// for `interval` via `sleep`, and repeats until `on_reading` returns
+    /// `ControlFlow::Break`. Errors are passed on like readings and don't end
+    /// the loop. An expired session keeps failing here; use
+    /// `SessionManager::run_poll_loop` to have it renewed.
+    pub fn run_poll_loop<S, F>(
+        &mut self,
+        session_id: &str,
+        interval: Duration,
+        sleep: S,
+        on_reading: F,
+    ) where
+        S: FnMut(Duration),
+        F: FnMut(Result<GlucosReading, C>) -> ControlFlow<()>,
+    {
+        run_loop(
+            || self.get_current_glucose_reading(session_id),
+            interval,
+            sleep,
+            on_reading,
+        )
+    }
+}
+
+pub(crate) fn run_loop<T>(
+    mut fetch: impl FnMut() -> T,
+    interval: Duration,
+    mut sleep: impl FnMut(Duration),
+    mut on_reading: impl FnMut(T) -> ControlFlow<()>,
+) {
+    while on_reading(fetch()).is_continue() {
+        sleep(interval);
+    }
+}
+
+/// Fraction (0.0 to 1.0) of the 5-minute readings captured when polling every
+/// `poll_interval_secs` and requesting up to `max_count_per_poll` readings.
+///
+/// Each poll picks up at most `max_count_per_poll` readings, while
+/// `poll_interval_secs / 300` new readings appear between polls.
+pub fn coverage_for_interval(poll_interval_secs: u32, max_count_per_poll: u32) -> f32 {
+    if poll_interval_secs == 0 {
+        return if max_count_per_poll == 0 { 0.0 } else { 1.0 };
+    }
+
+    let readings_per_poll = poll_interval_secs as f32 / DEXCOM_READING_INTERVAL_SECS as f32;
+    (max_count_per_poll as f32 / readings_per_poll).min(1.0)
+}
+
+/// When the reading after one with wall time `last_wt_ms` is due, so a
+/// scheduler can poll just after it instead of at a fixed interval. Share
+/// usually serves it a little later than this.
+pub fn next_expected_reading_ms(last_wt_ms: i64) -> i64 {
+    last_wt_ms + DEXCOM_READING_INTERVAL_SECS as i64 * 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::client::{mock_client, HttpResponse, TestError};
+    use crate::testing::reading_json;
+
+    #[test]
+    fn test_poller_suppresses_duplicates() {
+        let mut client = mock_client();
+        let mut calls = 0;
+
+        client
+            .expect_post_request()
+            .returning(move |_, _, _, mut buf| {
+                let wall_time = if calls < 2 {
+                    1699110415000i64
+                } else {
+                    1699110715000
+                };
+                calls += 1;
+
+                let body = format!("[{}]", reading_json(153, wall_time));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut poller = GlucosePoller::new(Dexcom::new(&mut client), "session");
+
+        assert_eq!(poller.poll().unwrap().unwrap().wall_time, 1699110415000);
+        assert_eq!(poller.poll().unwrap(), None);
+        assert_eq!(poller.poll().unwrap().unwrap().wall_time, 1699110715000);
+        assert_eq!(poller.poll().unwrap(), None);
+    }
+
+    #[test]
+    fn test_run_poll_loop() {
+        let mut client = mock_client();
+        let mut calls = 0;
+
+        client
+            .expect_post_request()
+            .times(3)
+            .returning(move |_, _, _, mut buf| {
+                calls += 1;
+                if calls == 2 {
+                    return Err(TestError);
+                }
+
+                let body = format!("[{}]", reading_json(153, 1699110415000));
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut sleeps = Vec::new();
+        let mut outcomes = Vec::new();
+        Dexcom::new(&mut client).run_poll_loop(
+            "session",
+            Duration::from_secs(300),
+            |delay| sleeps.push(delay),
+            |result| {
+                outcomes.push(result.map(|reading| reading.value).ok());
+                if outcomes.len() == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        );
+
+        assert_eq!(outcomes, [Some(153), None, Some(153)]);
+        assert_eq!(sleeps, [Duration::from_secs(300); 2]);
+    }
+
+    #[test]
+    fn test_coverage_for_interval() {
+        assert!((coverage_for_interval(300, 1) - 1.0).abs() < 0.001);
+        assert!((coverage_for_interval(900, 1) - 0.333).abs() < 0.001);
+        assert!((coverage_for_interval(900, 3) - 1.0).abs() < 0.001);
+        assert!((coverage_for_interval(60, 1) - 1.0).abs() < 0.001);
+        assert_eq!(coverage_for_interval(300, 0), 0.0);
+    }
+
+    #[test]
+    fn test_next_expected_reading_ms() {
+        assert_eq!(next_expected_reading_ms(1699110415000), 1699110715000);
+    }
+}
@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+const MASK: &str = "***";
+
+/// Renders a serialized request body for logging with the `password` field
+/// masked. The body is re-parsed rather than pattern-matched so passwords
+/// containing quotes or escapes can't break out of the redaction.
+pub(crate) fn request_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            if let Some(password) = value.get_mut("password") {
+                *password = Value::String(MASK.to_owned());
+            }
+            uuids(&value.to_string())
+        }
+        Err(_) => String::from("<unparseable body>"),
+    }
+}
+
+/// Renders a response body for logging. The authenticate and login
+/// responses are the account and session ids themselves, and either is as
+/// good as the password, so every UUID in the body is masked.
+pub(crate) fn response_body(body: &[u8]) -> String {
+    uuids(&String::from_utf8_lossy(body))
+}
+
+/// Masks every UUID in `text`, e.g. session ids in request bodies and URLs.
+pub(crate) fn uuids(text: &str) -> String {
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.get(..36).is_some_and(crate::is_uuid) {
+            masked.push_str(MASK);
+            rest = &rest[36..];
+        } else {
+            masked.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_body_masks_password() {
+        let password = r#"p"a\ss}"word"#;
+        let body = serde_json::to_vec(&serde_json::json!({
+            "accountName": "user",
+            "password": password,
+            "applicationId": "app",
+        }))
+        .unwrap();
+
+        let logged = request_body(&body);
+
+        assert!(!logged.contains(password));
+        assert!(!logged.contains("a\\\\ss"));
+        assert!(logged.contains(r#""password":"***""#));
+        assert!(logged.contains(r#""accountName":"user""#));
+    }
+
+    #[test]
+    fn test_response_body_masks_uuids() {
+        let logged = response_body(b"\"a21d18db-a276-40bc-8337-77dcd02df53e\"");
+        assert_eq!(logged, "\"***\"");
+
+        let logged =
+            request_body(br#"{"sessionId":"A21D18DB-A276-40BC-8337-77DCD02DF53E","minutes":10}"#);
+        assert_eq!(logged, r#"{"minutes":10,"sessionId":"***"}"#);
+
+        let uri =
+            "https://share2.dexcom.com/x?sessionId=a21d18db-a276-40bc-8337-77dcd02df53e&minutes=10";
+        assert_eq!(
+            uuids(uri),
+            "https://share2.dexcom.com/x?sessionId=***&minutes=10"
+        );
+        assert_eq!(response_body("Value: 153 →".as_bytes()), "Value: 153 →");
+    }
+
+    #[test]
+    fn test_request_body_unparseable() {
+        assert_eq!(request_body(b"password=hunter2"), "<unparseable body>");
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+/// Exponential backoff: retry `n` (starting at 0) waits `base_delay * 2^n`,
+/// optionally spread by up to `± jitter`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RetryPolicy {
+    pub base_delay: Duration,
+    pub max_retries: u32,
+    pub jitter: Duration,
+    pub seed: u64,
+}
+
+impl RetryPolicy {
+    pub fn new(base_delay: Duration, max_retries: u32) -> Self {
+        Self {
+            base_delay,
+            max_retries,
+            jitter: Duration::ZERO,
+            seed: 0,
+        }
+    }
+
+    /// Offsets every delay by a pseudo-random amount in `[-jitter, +jitter]`
+    /// derived from `seed`, so clients seeded differently don't retry in
+    /// lockstep.
+    pub fn with_jitter(mut self, jitter: Duration, seed: u64) -> Self {
+        self.jitter = jitter;
+        self.seed = seed;
+        self
+    }
+
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(2u32.saturating_pow(retry));
+
+        let jitter = self.jitter.as_nanos().min(u64::MAX as u128 / 2) as u64;
+        if jitter == 0 {
+            return delay;
+        }
+
+        let offset = splitmix64(self.seed ^ u64::from(retry)) % (2 * jitter + 1);
+        if offset >= jitter {
+            delay.saturating_add(Duration::from_nanos(offset - jitter))
+        } else {
+            delay.saturating_sub(Duration::from_nanos(jitter - offset))
+        }
+    }
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::new(Duration::from_secs(2), 3);
+
+        assert_eq!(policy.delay(0), Duration::from_secs(2));
+        assert_eq!(policy.delay(1), Duration::from_secs(4));
+        assert_eq!(policy.delay(2), Duration::from_secs(8));
+        assert_eq!(
+            policy.delay(40),
+            Duration::from_secs(2).saturating_mul(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn test_jittered_delay() {
+        let jitter = Duration::from_millis(500);
+        let policy = RetryPolicy::new(Duration::from_secs(2), 3).with_jitter(jitter, 42);
+        let other = RetryPolicy::new(Duration::from_secs(2), 3).with_jitter(jitter, 7);
+
+        for retry in 0..3 {
+            let base = Duration::from_secs(2) * 2u32.pow(retry);
+            let delay = policy.delay(retry);
+
+            assert!(delay >= base - jitter && delay <= base + jitter);
+            assert_eq!(delay, policy.delay(retry));
+        }
+
+        assert_ne!(
+            (0..3).map(|n| policy.delay(n)).collect::<Vec<_>>(),
+            (0..3).map(|n| other.delay(n)).collect::<Vec<_>>()
+        );
+
+        let short = RetryPolicy::new(Duration::from_millis(100), 1).with_jitter(jitter, 42);
+        assert!(short.delay(0) <= Duration::from_millis(600));
+    }
+}
@@ -0,0 +1,507 @@
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use crate::client::Client;
+use crate::poll::run_loop;
+use crate::{ClientError, Credentials, Dexcom, GlucosReading};
+
+type Result<T, C> = std::result::Result<T, ClientError<<C as Client>::Error>>;
+
+/// Source of the current time in epoch milliseconds.
+pub trait Clock {
+    fn now_ms(&self) -> i64;
+}
+
+impl<F: Fn() -> i64> Clock for F {
+    fn now_ms(&self) -> i64 {
+        self()
+    }
+}
+
+/// `Clock` backed by `std::time::SystemTime`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as i64)
+    }
+}
+
+/// A session id and when it was obtained. Dexcom doesn't report an expiry,
+/// but sessions last roughly 24 hours, so callers can refresh ahead of that.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Session {
+    pub id: String,
+    pub obtained_at_ms: i64,
+}
+
+/// Where `SessionManager` persists the session id between runs, e.g. a
+/// keyring or secure enclave.
+///
+/// `load` is consulted once, when the manager is created; the manager then
+/// caches the id in memory for its lifetime. `store` receives every id the
+/// manager adopts, and `clear` is called whenever it drops one, so the store
+/// never hands a dead id to the next run.
+pub trait SessionStore {
+    fn load(&mut self) -> Option<String>;
+    fn store(&mut self, session_id: &str);
+    fn clear(&mut self);
+}
+
+/// `SessionStore` holding the id in memory, so nothing outlives the process.
+#[derive(Debug, Default, Clone)]
+pub struct MemorySessionStore(Option<String>);
+
+impl SessionStore for MemorySessionStore {
+    fn load(&mut self) -> Option<String> {
+        self.0.clone()
+    }
+
+    fn store(&mut self, session_id: &str) {
+        self.0 = Some(session_id.to_owned());
+    }
+
+    fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Keeps a session id alive for a set of credentials: logs in on first use,
+/// logs in again once when Dexcom reports the session expired, and, with
+/// `max_session_age_secs` set, re-authenticates once the session gets that
+/// old even if it still works.
+pub struct SessionManager<'a, C: Client, K: Clock, T: SessionStore = MemorySessionStore> {
+    dexcom: Dexcom<'a, C>,
+    credentials: Credentials,
+    clock: K,
+    store: T,
+    session: Option<Session>,
+    max_session_age_secs: Option<u32>,
+}
+
+impl<'a, C: Client, K: Clock> SessionManager<'a, C, K> {
+    pub fn new(dexcom: Dexcom<'a, C>, credentials: Credentials, clock: K) -> Self {
+        Self::with_store(dexcom, credentials, clock, MemorySessionStore::default())
+    }
+
+    /// Starts out with a session id persisted from an earlier run, so the
+    /// first read skips the login. A rejected id triggers the usual re-login.
+    pub fn with_session(
+        dexcom: Dexcom<'a, C>,
+        credentials: Credentials,
+        clock: K,
+        session_id: &str,
+    ) -> Self {
+        let mut manager = Self::new(dexcom, credentials, clock);
+        manager.set_session_id(session_id);
+        manager
+    }
+}
+
+impl<'a, C: Client, K: Clock, T: SessionStore> SessionManager<'a, C, K, T> {
+    /// Persists session ids through `store`, starting out with the one it
+    /// already holds, if any. Its age counts from now.
+    pub fn with_store(
+        dexcom: Dexcom<'a, C>,
+        credentials: Credentials,
+        clock: K,
+        mut store: T,
+    ) -> Self {
+        let session = store.load().map(|id| Session {
+            id,
+            obtained_at_ms: clock.now_ms(),
+        });
+
+        Self {
+            dexcom,
+            credentials,
+            clock,
+            store,
+            session,
+            max_session_age_secs: None,
+        }
+    }
+
+    /// Replaces the cached session id and writes it to the store. Its age for
+    /// `max_session_age_secs` counts from now.
+    pub fn set_session_id(&mut self, session_id: &str) {
+        self.store.store(session_id);
+        self.session = Some(Session {
+            id: session_id.to_owned(),
+            obtained_at_ms: self.clock.now_ms(),
+        });
+    }
+
+    pub fn set_max_session_age_secs(&mut self, max_session_age_secs: Option<u32>) {
+        self.max_session_age_secs = max_session_age_secs;
+    }
+
+    pub fn dexcom(&mut self) -> &mut Dexcom<'a, C> {
+        &mut self.dexcom
+    }
+
+    /// Drops the cached session and clears the store, so the next call logs
+    /// in again.
+    pub fn invalidate(&mut self) {
+        self.session = None;
+        self.store.clear();
+    }
+
+    /// Returns the cached session id, logging in first when there is none or
+    /// it is older than `max_session_age_secs`.
+    pub fn session_id(&mut self) -> Result<&str, C> {
+        let now_ms = self.clock.now_ms();
+        let expired = match (&self.session, self.max_session_age_secs) {
+            (None, _) => true,
+            (Some(Session { obtained_at_ms, .. }), Some(max_age)) => {
+                now_ms - obtained_at_ms >= max_age as i64 * 1000
+            }
+            (Some(_), None) => false,
+        };
+
+        if expired {
+            self.login(now_ms)?;
+        }
+
+        Ok(self
+            .session
+            .as_ref()
+            .map_or("", |session| session.id.as_str()))
+    }
+
+    /// Logs in first if needed. The login and the read count as a single call
+    /// against `Dexcom::set_min_request_interval`.
+    pub fn get_current_glucose_reading(&mut self) -> Result<GlucosReading, C> {
+        let outermost = self.dexcom.begin_operation();
+        let result = self.read_current();
+        self.dexcom.end_operation(outermost);
+        result
+    }
+
+    fn read_current(&mut self) -> Result<GlucosReading, C> {
+        let session_id = self.session_id()?.to_owned();
+
+        match self.dexcom.get_current_glucose_reading(&session_id) {
+            Err(ClientError::DexcomError(error, _)) if error.is_session_error() => {
+                self.login(self.clock.now_ms())?;
+                let session_id = self.session_id()?.to_owned();
+                self.dexcom.get_current_glucose_reading(&session_id)
+            }
+            result => result,
+        }
+    }
+
+    /// `Dexcom::run_poll_loop`, logging in again whenever the session
+    /// expires.
+    pub fn run_poll_loop<S, F>(&mut self, interval: Duration, sleep: S, on_reading: F)
+    where
+        S: FnMut(Duration),
+        F: FnMut(Result<GlucosReading, C>) -> ControlFlow<()>,
+    {
+        run_loop(
+            || self.get_current_glucose_reading(),
+            interval,
+            sleep,
+            on_reading,
+        )
+    }
+
+    fn login(&mut self, now_ms: i64) -> Result<(), C> {
+        self.invalidate();
+
+        let Credentials {
+            account_name,
+            password,
+            application_id,
+        } = &self.credentials;
+        let session = self
+            .dexcom
+            .load_session(account_name, password, application_id, now_ms)?;
+
+        self.store.store(&session.id);
+        self.session = Some(session);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io::Write;
+
+    use mockall::predicate::*;
+
+    use super::*;
+    use crate::client::{mock_client, HttpResponse};
+    use crate::testing::*;
+
+    fn latest_reading() -> String {
+        format!("[{}]", reading_json(153, 1699110415000))
+    }
+
+    #[test]
+    fn test_max_session_age_forces_reauth() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            "/AuthenticatePublisherAccount",
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        )
+        .times(2);
+        expect_response(
+            &mut client,
+            "/LoginPublisherAccountById",
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        )
+        .times(2);
+        expect_response(
+            &mut client,
+            "/ReadPublisherLatestGlucoseValues",
+            200,
+            latest_reading(),
+        )
+        .times(3);
+
+        let now = Cell::new(0);
+        let mut session = SessionManager::new(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || now.get(),
+        );
+        session.set_max_session_age_secs(Some(3600));
+
+        session.get_current_glucose_reading().unwrap();
+
+        now.set(3_599_000);
+        session.get_current_glucose_reading().unwrap();
+
+        now.set(3_600_000);
+        assert_eq!(session.get_current_glucose_reading().unwrap().value, 153);
+    }
+
+    #[test]
+    fn test_expired_session_logs_in_again() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            "/AuthenticatePublisherAccount",
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        )
+        .times(2);
+        expect_response(
+            &mut client,
+            "/LoginPublisherAccountById",
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        )
+        .times(2);
+
+        let mut calls = 0;
+        client
+            .expect_post_request()
+            .withf(|uri, _, _, _| uri.ends_with("/ReadPublisherLatestGlucoseValues"))
+            .times(2)
+            .returning(move |_, _, _, mut buf| {
+                calls += 1;
+                let (status, body) = match calls {
+                    1 => (500, r#"{"Code":"SessionNotValid"}"#.to_owned()),
+                    _ => (200, latest_reading()),
+                };
+                Ok(HttpResponse {
+                    body_len: buf.write(body.as_bytes()).unwrap(),
+                    status,
+                    location: None,
+                })
+            });
+
+        let mut session = SessionManager::new(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || 0,
+        );
+
+        assert_eq!(session.get_current_glucose_reading().unwrap().value, 153);
+    }
+
+    #[test]
+    fn test_seeded_session_is_used() {
+        let mut client = mock_client();
+        client
+            .expect_post_request()
+            .withf(|uri, _, body, _| {
+                uri.ends_with("/ReadPublisherLatestGlucoseValues")
+                    && body.starts_with(br#"{"sessionId":"a21d18db-a276-40bc-8337-77dcd02df53e""#)
+            })
+            .times(1)
+            .returning(|_, _, _, mut buf| {
+                Ok(HttpResponse {
+                    body_len: buf.write(latest_reading().as_bytes()).unwrap(),
+                    status: 200,
+                    location: None,
+                })
+            });
+
+        let mut session = SessionManager::with_session(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || 0,
+            "a21d18db-a276-40bc-8337-77dcd02df53e",
+        );
+
+        assert_eq!(session.get_current_glucose_reading().unwrap().value, 153);
+    }
+
+    #[derive(Default)]
+    struct RecordingStore {
+        session_id: Option<String>,
+        calls: Vec<String>,
+    }
+
+    impl SessionStore for &mut RecordingStore {
+        fn load(&mut self) -> Option<String> {
+            self.calls.push("load".to_owned());
+            self.session_id.clone()
+        }
+
+        fn store(&mut self, session_id: &str) {
+            self.calls.push(format!("store {}", session_id));
+            self.session_id = Some(session_id.to_owned());
+        }
+
+        fn clear(&mut self) {
+            self.calls.push("clear".to_owned());
+            self.session_id = None;
+        }
+    }
+
+    #[test]
+    fn test_session_store() {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            "/AuthenticatePublisherAccount",
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        )
+        .times(1);
+        expect_response(
+            &mut client,
+            "/LoginPublisherAccountById",
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        )
+        .times(1);
+        expect_response(
+            &mut client,
+            "/ReadPublisherLatestGlucoseValues",
+            200,
+            latest_reading(),
+        )
+        .times(2);
+
+        let mut store = RecordingStore::default();
+        let mut session = SessionManager::with_store(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || 0,
+            &mut store,
+        );
+        session.get_current_glucose_reading().unwrap();
+        drop(session);
+
+        assert_eq!(
+            store.calls,
+            [
+                "load",
+                "clear",
+                "store a21d18db-a276-40bc-8337-77dcd02df53e"
+            ]
+        );
+
+        // A stored id is picked up without logging in again.
+        let mut session = SessionManager::with_store(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || 0,
+            &mut store,
+        );
+        assert_eq!(session.get_current_glucose_reading().unwrap().value, 153);
+
+        session.set_session_id("b0f31e9c-90a5-4c5e-9d6a-2f5b1e8d7c3a");
+        session.invalidate();
+        drop(session);
+
+        assert_eq!(
+            store.calls[3..],
+            [
+                "load",
+                "store b0f31e9c-90a5-4c5e-9d6a-2f5b1e8d7c3a",
+                "clear"
+            ]
+        );
+        assert_eq!(store.session_id, None);
+    }
+
+    #[test]
+    fn test_stale_seeded_session_logs_in() {
+        for error in [
+            r#"{"Code":"SessionIdNotFound"}"#,
+            r#"{"Code":"InvalidArgument","Message":"Expected a non-empty string. Parameter name: sessionId"}"#,
+        ] {
+            stale_seeded_session_logs_in(error);
+        }
+    }
+
+    fn stale_seeded_session_logs_in(error: &'static str) {
+        let mut client = mock_client();
+        expect_response(
+            &mut client,
+            "/AuthenticatePublisherAccount",
+            200,
+            "\"1e913fce-5a34-4d27-a991-b6cb3a3bd3d8\"",
+        )
+        .times(1);
+        expect_response(
+            &mut client,
+            "/LoginPublisherAccountById",
+            200,
+            "\"a21d18db-a276-40bc-8337-77dcd02df53e\"",
+        )
+        .times(1);
+        client
+            .expect_post_request()
+            .withf(|uri, _, _, _| uri.ends_with("/ReadPublisherLatestGlucoseValues"))
+            .times(2)
+            .returning(move |_, _, body, mut buf| {
+                let (status, response) = if body.starts_with(br#"{"sessionId":"stale""#) {
+                    (500, error.to_owned())
+                } else {
+                    (200, latest_reading())
+                };
+                Ok(HttpResponse {
+                    body_len: buf.write(response.as_bytes()).unwrap(),
+                    status,
+                    location: None,
+                })
+            });
+
+        let mut session = SessionManager::with_session(
+            Dexcom::new(&mut client),
+            Credentials::new("user", "pass", "app"),
+            || 0,
+            "stale",
+        );
+
+        assert_eq!(session.get_current_glucose_reading().unwrap().value, 153);
+        assert_eq!(
+            session.session_id().unwrap(),
+            "a21d18db-a276-40bc-8337-77dcd02df53e"
+        );
+    }
+}
@@ -0,0 +1,941 @@
+use crate::unit::GlucoseUnit;
+use crate::{GlucosReading, Trend};
+
+pub const TARGET_RANGE_LOW: i32 = 70;
+pub const TARGET_RANGE_HIGH: i32 = 180;
+
+/// Summary statistics over a set of readings.
+///
+/// `time_in_range` is the fraction (0.0 to 1.0) of readings within
+/// `TARGET_RANGE_LOW..=TARGET_RANGE_HIGH`. An empty set yields all zeros.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct GlucoseStats {
+    pub count: usize,
+    pub mean: f32,
+    pub min: i32,
+    pub max: i32,
+    pub time_in_range: f32,
+}
+
+impl GlucoseStats {
+    pub fn from_readings(readings: &[GlucosReading]) -> Self {
+        if readings.is_empty() {
+            return Self::default();
+        }
+
+        let count = readings.len();
+        let sum: i64 = readings.iter().map(|r| r.value as i64).sum();
+        let in_range = readings.iter().filter(|r| is_in_range(r.value)).count();
+
+        Self {
+            count,
+            mean: sum as f32 / count as f32,
+            min: readings.iter().map(|r| r.value).min().unwrap_or_default(),
+            max: readings.iter().map(|r| r.value).max().unwrap_or_default(),
+            time_in_range: in_range as f32 / count as f32,
+        }
+    }
+}
+
+/// Average, extremes and newest reading of a window, from `summarize`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GlucoseSummary {
+    pub average: f32,
+    pub min: i32,
+    pub max: i32,
+    /// The reading with the newest wall time; the last one among equals.
+    pub latest: GlucosReading,
+    pub count: usize,
+}
+
+/// Summarizes `readings` in a single pass, `None` when empty.
+pub fn summarize(readings: &[GlucosReading]) -> Option<GlucoseSummary> {
+    let (first, rest) = readings.split_first()?;
+
+    let mut summary = GlucoseSummary {
+        average: 0.0,
+        min: first.value,
+        max: first.value,
+        latest: *first,
+        count: readings.len(),
+    };
+    let mut sum = first.value as i64;
+
+    for reading in rest {
+        sum += reading.value as i64;
+        summary.min = summary.min.min(reading.value);
+        summary.max = summary.max.max(reading.value);
+        if reading.wall_time >= summary.latest.wall_time {
+            summary.latest = *reading;
+        }
+    }
+
+    summary.average = sum as f32 / summary.count as f32;
+    Some(summary)
+}
+
+/// Reading counts below, within and above a target range, with the matching
+/// percentages (0.0 to 100.0). An empty set yields all zeros.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct TimeInRange {
+    pub below: usize,
+    pub in_range: usize,
+    pub above: usize,
+    pub below_percent: f32,
+    pub in_range_percent: f32,
+    pub above_percent: f32,
+}
+
+/// Splits readings by `low..=high`, e.g. `TARGET_RANGE_LOW..=TARGET_RANGE_HIGH`.
+pub fn time_in_range(readings: &[GlucosReading], low: i32, high: i32) -> TimeInRange {
+    if readings.is_empty() {
+        return TimeInRange::default();
+    }
+
+    let below = readings.iter().filter(|r| r.value < low).count();
+    let above = readings.iter().filter(|r| r.value > high).count();
+    let in_range = readings.len() - below - above;
+    let percent = |count: usize| count as f32 * 100.0 / readings.len() as f32;
+
+    TimeInRange {
+        below,
+        in_range,
+        above,
+        below_percent: percent(below),
+        in_range_percent: percent(in_range),
+        above_percent: percent(above),
+    }
+}
+
+/// Cumulative statistics updated in O(1) per reading.
+///
+/// Every pushed reading counts towards the result forever. Min/max over a
+/// sliding window would need a different structure (e.g. a monotonic deque),
+/// which this accumulator deliberately does not keep.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RollingStats {
+    count: usize,
+    sum: i64,
+    min: i32,
+    max: i32,
+    in_range: usize,
+}
+
+impl RollingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, reading: GlucosReading) {
+        let value = reading.value;
+
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+
+        self.count += 1;
+        self.sum += value as i64;
+        if is_in_range(value) {
+            self.in_range += 1;
+        }
+    }
+
+    pub fn stats(&self) -> GlucoseStats {
+        if self.count == 0 {
+            return GlucoseStats::default();
+        }
+
+        GlucoseStats {
+            count: self.count,
+            mean: self.sum as f32 / self.count as f32,
+            min: self.min,
+            max: self.max,
+            time_in_range: self.in_range as f32 / self.count as f32,
+        }
+    }
+}
+
+/// Value change in mg/dL between the two most recent readings.
+///
+/// `readings` is expected newest-first, the order the Share API returns.
+pub fn delta(readings: &[GlucosReading]) -> Option<i32> {
+    match readings {
+        [latest, previous, ..] => Some(latest.value - previous.value),
+        _ => None,
+    }
+}
+
+/// Rate of change between the two most recent readings in `unit` per
+/// minute, e.g. `+2.0 mg/dL/min`. `None` with fewer than two readings or
+/// when they share a wall time.
+///
+/// `readings` is expected newest-first, the order the Share API returns.
+pub fn rate_label(readings: &[GlucosReading], unit: GlucoseUnit) -> Option<String> {
+    let [latest, previous, ..] = readings else {
+        return None;
+    };
+    if latest.wall_time == previous.wall_time {
+        return None;
+    }
+
+    let minutes = (latest.wall_time - previous.wall_time) as f32 / 60_000.0;
+    let rate = unit.from_mg_dl(latest.value - previous.value) / minutes;
+    Some(format!("{:+.1} {}/min", rate, unit.symbol()))
+}
+
+/// Whether any two adjacent readings are more than `max_gap_min` minutes
+/// apart by wall time, i.e. readings went missing in between.
+pub fn has_signal_gap(readings: &[GlucosReading], max_gap_min: i64) -> bool {
+    readings
+        .windows(2)
+        .any(|pair| (pair[0].wall_time - pair[1].wall_time).abs() > max_gap_min * 60_000)
+}
+
+/// Whether the latest reading's trend points the same way as the value change
+/// from the previous reading: up for a rise, down for a drop, and `Flat` for a
+/// change within its ±1 mg/dL per minute band. `None` with fewer than two
+/// readings or when the latest trend has no direction.
+///
+/// `readings` is expected newest-first, the order the Share API returns.
+pub fn trend_agrees(readings: &[GlucosReading]) -> Option<bool> {
+    let [latest, previous, ..] = readings else {
+        return None;
+    };
+    let rate = latest.trend.rate_per_minute()?;
+    let delta = latest.value - previous.value;
+
+    Some(if rate > 0.0 {
+        delta > 0
+    } else if rate < 0.0 {
+        delta < 0
+    } else {
+        let minutes = ((latest.wall_time - previous.wall_time).abs() as f32 / 60_000.0).max(1.0);
+        (delta as f32 / minutes).abs() <= 1.0
+    })
+}
+
+/// Number of consecutive most-recent readings within `low..=high`.
+///
+/// `readings` is expected newest-first, the order the Share API returns.
+pub fn in_range_streak(readings: &[GlucosReading], low: i32, high: i32) -> u32 {
+    readings
+        .iter()
+        .take_while(|reading| (low..=high).contains(&reading.value))
+        .count() as u32
+}
+
+/// Buckets readings into weekday x hour-of-day cells of local time and
+/// computes stats per cell, e.g. for an ambulatory glucose profile heatmap.
+///
+/// The outer index is the weekday starting at Monday (0) through Sunday (6),
+/// the inner index is the hour (0-23). Local time is the reading's wall time
+/// shifted by `tz_offset_minutes` east of UTC.
+pub fn by_weekday_hour(
+    readings: &[GlucosReading],
+    tz_offset_minutes: i16,
+) -> [[GlucoseStats; 24]; 7] {
+    const MS_PER_DAY: i64 = 86_400_000;
+    const MS_PER_HOUR: i64 = 3_600_000;
+
+    let mut cells = [[RollingStats::new(); 24]; 7];
+
+    for reading in readings {
+        let local = reading.wall_time + tz_offset_minutes as i64 * 60_000;
+        // 1970-01-01 was a Thursday.
+        let weekday = (local.div_euclid(MS_PER_DAY) + 3).rem_euclid(7) as usize;
+        let hour = (local.rem_euclid(MS_PER_DAY) / MS_PER_HOUR) as usize;
+        cells[weekday][hour].push(*reading);
+    }
+
+    cells.map(|day| day.map(|cell| cell.stats()))
+}
+
+/// Candlestick-style aggregate of one time bucket.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Ohlc {
+    /// Start of the bucket in epoch ms.
+    pub time: i64,
+    pub open: i32,
+    pub high: i32,
+    pub low: i32,
+    pub close: i32,
+}
+
+/// Aggregates readings into `bucket_secs`-wide buckets aligned to the epoch,
+/// oldest bucket first. Buckets without readings are skipped.
+pub fn ohlc_buckets(readings: &[GlucosReading], bucket_secs: u32) -> Vec<Ohlc> {
+    let bucket_ms = bucket_secs as i64 * 1000;
+    if bucket_ms == 0 {
+        return Vec::new();
+    }
+
+    let mut sorted = readings.to_vec();
+    sorted.sort_by_key(|r| r.wall_time);
+
+    let mut buckets: Vec<Ohlc> = Vec::new();
+    for reading in sorted {
+        let time = reading.wall_time - reading.wall_time.rem_euclid(bucket_ms);
+
+        match buckets.last_mut() {
+            Some(bucket) if bucket.time == time => {
+                bucket.high = bucket.high.max(reading.value);
+                bucket.low = bucket.low.min(reading.value);
+                bucket.close = reading.value;
+            }
+            _ => buckets.push(Ohlc {
+                time,
+                open: reading.value,
+                high: reading.value,
+                low: reading.value,
+                close: reading.value,
+            }),
+        }
+    }
+
+    buckets
+}
+
+const SLOT_MS: i64 = 5 * 60_000;
+
+/// Places readings into 5-minute slots at `start_ms`, `start_ms + 5 min`, ...
+/// up to `end_ms`, oldest first, for plotting. Each reading goes to its
+/// nearest slot and, when two share one, the newest wins. Slots without a
+/// reading are `None`; readings outside the span are dropped.
+pub fn bucketize(
+    readings: &[GlucosReading],
+    start_ms: i64,
+    end_ms: i64,
+) -> Vec<Option<GlucosReading>> {
+    if end_ms < start_ms {
+        return Vec::new();
+    }
+
+    let mut slots = vec![None; ((end_ms - start_ms) / SLOT_MS + 1) as usize];
+    for reading in readings {
+        let offset = reading.wall_time - start_ms;
+        let index = (offset + SLOT_MS / 2).div_euclid(SLOT_MS);
+        if offset < -SLOT_MS / 2 || index as usize >= slots.len() {
+            continue;
+        }
+
+        let slot: &mut Option<GlucosReading> = &mut slots[index as usize];
+        if slot.is_none_or(|current| current.wall_time < reading.wall_time) {
+            *slot = Some(*reading);
+        }
+    }
+
+    slots
+}
+
+/// A rise-and-fall episode found by `excursions`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Excursion {
+    /// Wall time (epoch ms) of the baseline reading the rise started from.
+    pub start: i64,
+    /// Wall time (epoch ms) of the first reading back at or below baseline.
+    pub end: i64,
+    pub baseline: i32,
+    pub peak: GlucosReading,
+    /// Area above baseline in mg/dL x minutes (trapezoidal rule).
+    pub auc: f32,
+}
+
+/// Detects distinct rise-and-fall episodes.
+///
+/// Readings are walked in chronological order while tracking the lowest
+/// value seen as the baseline. An excursion starts once a reading is at least
+/// `rise_threshold` above the baseline and ends at the first reading that
+/// falls back to the baseline value or below; that reading becomes the next
+/// baseline. An episode that has not returned to baseline by the last reading
+/// is not reported.
+pub fn excursions(readings: &[GlucosReading], rise_threshold: i32) -> Vec<Excursion> {
+    let mut sorted = readings.to_vec();
+    sorted.sort_by_key(|r| r.wall_time);
+
+    let mut found = Vec::new();
+    let mut baseline = 0;
+    let mut peak = None;
+
+    for (index, reading) in sorted.iter().enumerate().skip(1) {
+        let base = sorted[baseline].value;
+
+        match peak {
+            None if reading.value < base => baseline = index,
+            None if reading.value - base >= rise_threshold => peak = Some(index),
+            None => {}
+            Some(peak_index) if reading.value <= base => {
+                let episode = &sorted[baseline..=index];
+                found.push(Excursion {
+                    start: sorted[baseline].wall_time,
+                    end: reading.wall_time,
+                    baseline: base,
+                    peak: sorted[peak_index],
+                    auc: area_above(episode, base),
+                });
+                baseline = index;
+                peak = None;
+            }
+            Some(peak_index) if reading.value > sorted[peak_index].value => peak = Some(index),
+            Some(_) => {}
+        }
+    }
+
+    found
+}
+
+fn area_above(readings: &[GlucosReading], baseline: i32) -> f32 {
+    readings
+        .windows(2)
+        .map(|pair| {
+            let minutes = (pair[1].wall_time - pair[0].wall_time) as f32 / 60_000.0;
+            let a = (pair[0].value - baseline).max(0) as f32;
+            let b = (pair[1].value - baseline).max(0) as f32;
+            (a + b) / 2.0 * minutes
+        })
+        .sum()
+}
+
+/// Glucose Management Indicator, the estimated A1C in percent:
+/// `3.31 + 0.02392 * mean mg/dL`. Returns `None` for an empty slice.
+pub fn gmi(readings: &[GlucosReading]) -> Option<f32> {
+    if readings.is_empty() {
+        return None;
+    }
+
+    Some(3.31 + 0.02392 * GlucoseStats::from_readings(readings).mean)
+}
+
+/// Mean Amplitude of Glycemic Excursions.
+///
+/// Readings are taken in chronological order and the threshold is one
+/// population standard deviation of all their values. A peak or nadir only
+/// counts once the series has moved back from it by more than that, so
+/// smaller wiggles don't split one excursion into two; the first excursion
+/// starts at the lowest or highest value before it. MAGE is the mean
+/// amplitude of the excursions between consecutive turning points.
+///
+/// The result is sensitive to the SD threshold: a flat series with one large
+/// swing has a small SD and lets minor swings count too. Returns `None` with
+/// fewer than three readings or when no swing exceeds the threshold.
+pub fn mage(readings: &[GlucosReading]) -> Option<f32> {
+    if readings.len() < 3 {
+        return None;
+    }
+
+    let mut sorted = readings.to_vec();
+    sorted.sort_by_key(|r| r.wall_time);
+    let values: Vec<i32> = sorted.iter().map(|r| r.value).collect();
+
+    let count = values.len() as f32;
+    let mean = values.iter().sum::<i32>() as f32 / count;
+    let variance = values
+        .iter()
+        .map(|value| (*value as f32 - mean).powi(2))
+        .sum::<f32>()
+        / count;
+    let sd = variance.sqrt();
+
+    let swing = |from: i32, to: i32| (to - from).abs() as f32;
+    let (mut low, mut high) = (values[0], values[0]);
+    let mut turning_points = Vec::new();
+    let mut extreme = values[0];
+    let mut rising = None;
+
+    for &value in &values[1..] {
+        match rising {
+            None => {
+                low = low.min(value);
+                high = high.max(value);
+                if swing(low, high) > sd {
+                    rising = Some(value == high);
+                    turning_points.push(if value == high { low } else { high });
+                    extreme = value;
+                }
+            }
+            Some(true) if value >= extreme => extreme = value,
+            Some(false) if value <= extreme => extreme = value,
+            Some(up) if swing(extreme, value) > sd => {
+                turning_points.push(extreme);
+                extreme = value;
+                rising = Some(!up);
+            }
+            Some(_) => {}
+        }
+    }
+
+    turning_points.push(extreme);
+
+    let amplitudes: Vec<f32> = turning_points
+        .windows(2)
+        .map(|pair| swing(pair[0], pair[1]))
+        .collect();
+
+    if amplitudes.is_empty() {
+        return None;
+    }
+
+    Some(amplitudes.iter().sum::<f32>() / amplitudes.len() as f32)
+}
+
+/// Trend arrow computed locally from the least-squares slope of the
+/// readings' values over their wall times, in mg/dL per minute:
+///
+/// | rate          | trend         |
+/// |---------------|---------------|
+/// | > 3           | DoubleUp      |
+/// | 2 to 3        | SingleUp      |
+/// | 1 to 2        | FortyFiveUp   |
+/// | -1 to 1       | Flat          |
+/// | -2 to -1      | FortyFiveDown |
+/// | -3 to -2      | SingleDown    |
+/// | < -3          | DoubleDown    |
+///
+/// A rate exactly on a boundary goes to the steeper arrow, except ±3 which
+/// stays Single. Fewer than two readings, or readings all at the same time,
+/// yield `NotComputable`.
+pub fn computed_trend(readings: &[GlucosReading]) -> Trend {
+    if readings.len() < 2 {
+        return Trend::NotComputable;
+    }
+
+    let count = readings.len() as f64;
+    let origin = readings[0].wall_time;
+    let points: Vec<(f64, f64)> = readings
+        .iter()
+        .map(|r| ((r.wall_time - origin) as f64 / 60_000.0, r.value as f64))
+        .collect();
+
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / count;
+    let mean_v = points.iter().map(|(_, v)| v).sum::<f64>() / count;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (t, v)| {
+        (
+            cov + (t - mean_t) * (v - mean_v),
+            var + (t - mean_t).powi(2),
+        )
+    });
+
+    if variance == 0.0 {
+        return Trend::NotComputable;
+    }
+
+    match covariance / variance {
+        rate if rate > 3.0 => Trend::DoubleUp,
+        rate if rate >= 2.0 => Trend::SingleUp,
+        rate if rate >= 1.0 => Trend::FortyFiveUp,
+        rate if rate > -1.0 => Trend::Flat,
+        rate if rate > -2.0 => Trend::FortyFiveDown,
+        rate if rate >= -3.0 => Trend::SingleDown,
+        _ => Trend::DoubleDown,
+    }
+}
+
+/// Replaces `Trend::None` with the `computed_trend` of the reading and its
+/// immediate neighbors, tagging Share readings `Source::Computed`. The other
+/// sentinels are left alone, as is a `None` whose neighborhood yields
+/// `NotComputable`.
+pub fn fill_trends(readings: &mut [GlucosReading]) {
+    for index in 0..readings.len() {
+        if readings[index].trend != Trend::None {
+            continue;
+        }
+
+        let window = index.saturating_sub(1)..(index + 2).min(readings.len());
+        match computed_trend(&readings[window]) {
+            Trend::NotComputable => {}
+            trend => {
+                readings[index].trend = trend;
+                readings[index].source = readings[index].source.computed();
+            }
+        }
+    }
+}
+
+fn is_in_range(value: i32) -> bool {
+    (TARGET_RANGE_LOW..=TARGET_RANGE_HIGH).contains(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{reading, reading_at};
+    use crate::Source;
+
+    #[test]
+    fn test_rolling_stats_matches_batch() {
+        let readings: Vec<_> = [65, 120, 153, 190, 101].into_iter().map(reading).collect();
+
+        let mut rolling = RollingStats::new();
+        for (i, r) in readings.iter().enumerate() {
+            rolling.push(*r);
+            assert_eq!(
+                rolling.stats(),
+                GlucoseStats::from_readings(&readings[..=i])
+            );
+        }
+
+        let stats = rolling.stats();
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.min, 65);
+        assert_eq!(stats.max, 190);
+        assert!((stats.mean - 125.8).abs() < 0.001);
+        assert!((stats.time_in_range - 0.6).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_time_in_range() {
+        let readings: Vec<_> = [55, 69, 70, 120, 180, 181, 250, 140]
+            .into_iter()
+            .map(reading)
+            .collect();
+
+        let tir = time_in_range(&readings, TARGET_RANGE_LOW, TARGET_RANGE_HIGH);
+        assert_eq!((tir.below, tir.in_range, tir.above), (2, 4, 2));
+        assert_eq!(tir.below_percent, 25.0);
+        assert_eq!(tir.in_range_percent, 50.0);
+        assert_eq!(tir.above_percent, 25.0);
+
+        let tight = time_in_range(&readings, 70, 140);
+        assert_eq!((tight.below, tight.in_range, tight.above), (2, 3, 3));
+        assert_eq!(tight.in_range_percent, 37.5);
+
+        assert_eq!(time_in_range(&[], 70, 180), TimeInRange::default());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let readings = [
+            reading_at(150, 10 * 60_000),
+            reading_at(95, 20 * 60_000),
+            reading_at(180, 0),
+            reading_at(121, 5 * 60_000),
+        ];
+
+        let summary = summarize(&readings).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.min, 95);
+        assert_eq!(summary.max, 180);
+        assert!((summary.average - 136.5).abs() < 0.001);
+        assert_eq!(summary.latest, readings[1]);
+
+        let same_time = [reading(100), reading(110)];
+        assert_eq!(summarize(&same_time).unwrap().latest.value, 110);
+        assert_eq!(summarize(&[]), None);
+    }
+
+    #[test]
+    fn test_delta() {
+        assert_eq!(delta(&[]), None);
+        assert_eq!(delta(&[reading(120)]), None);
+        assert_eq!(delta(&[reading(130), reading(120), reading(90)]), Some(10));
+        assert_eq!(delta(&[reading(100), reading(120)]), Some(-20));
+    }
+
+    #[test]
+    fn test_rate_label() {
+        let rising = [reading_at(130, 600_000), reading_at(120, 300_000)];
+        let falling = [reading_at(110, 600_000), reading_at(120, 300_000)];
+
+        assert_eq!(
+            rate_label(&rising, GlucoseUnit::MgDl).as_deref(),
+            Some("+2.0 mg/dL/min")
+        );
+        assert_eq!(
+            rate_label(&rising, GlucoseUnit::MmolL).as_deref(),
+            Some("+0.1 mmol/L/min")
+        );
+        assert_eq!(
+            rate_label(&falling, GlucoseUnit::MgDl).as_deref(),
+            Some("-2.0 mg/dL/min")
+        );
+        assert_eq!(
+            rate_label(&falling, GlucoseUnit::MmolL).as_deref(),
+            Some("-0.1 mmol/L/min")
+        );
+        assert_eq!(rate_label(&rising[..1], GlucoseUnit::MgDl), None);
+        assert_eq!(
+            rate_label(&[reading(130), reading(120)], GlucoseUnit::MgDl),
+            None
+        );
+    }
+
+    #[test]
+    fn test_by_weekday_hour() {
+        // Sat, 04 Nov 2023 15:06:55 UTC
+        let saturday = 1699110415000;
+        let readings = [
+            reading_at(150, saturday),
+            reading_at(170, saturday + 10 * 60_000),
+            // 9 hours later: Sun 00:06 UTC
+            reading_at(90, saturday + 9 * 3_600_000),
+        ];
+
+        let utc = by_weekday_hour(&readings, 0);
+        assert_eq!(utc[5][15].count, 2);
+        assert_eq!(utc[5][15].mean, 160.0);
+        assert_eq!(utc[6][0].count, 1);
+        assert_eq!(utc[6][0].min, 90);
+
+        // UTC+9: Sun 00:06 and Sun 09:06
+        let kst = by_weekday_hour(&readings, 9 * 60);
+        assert_eq!(kst[6][0].count, 2);
+        assert_eq!(kst[6][9].count, 1);
+        assert_eq!(kst[5][15].count, 0);
+
+        let total: usize = kst.iter().flatten().map(|cell| cell.count).sum();
+        assert_eq!(total, 3);
+    }
+
+    #[test]
+    fn test_excursions() {
+        let values = [100, 95, 120, 150, 130, 95, 100, 140, 160, 110, 90, 100, 130];
+        let readings: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| reading_at(*value, i as i64 * 5 * 60_000))
+            .rev()
+            .collect();
+
+        let found = excursions(&readings, 30);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(found[0].baseline, 95);
+        assert_eq!(found[0].start, 5 * 60_000);
+        assert_eq!(found[0].end, 25 * 60_000);
+        assert_eq!(found[0].peak.value, 150);
+        // (0+25)/2*5 + (25+55)/2*5 + (55+35)/2*5 + (35+0)/2*5
+        assert!((found[0].auc - 575.0).abs() < 0.001);
+
+        assert_eq!(found[1].baseline, 95);
+        assert_eq!(found[1].start, 25 * 60_000);
+        assert_eq!(found[1].end, 50 * 60_000);
+        assert_eq!(found[1].peak.value, 160);
+
+        assert!(excursions(&readings[..1], 30).is_empty());
+    }
+
+    #[test]
+    fn test_gmi() {
+        let readings = [reading(100), reading(154), reading(208)];
+
+        // 3.31 + 0.02392 * 154
+        assert!((gmi(&readings).unwrap() - 6.99368).abs() < 0.0001);
+        assert!((gmi(&[reading(100)]).unwrap() - 5.702).abs() < 0.0001);
+        assert_eq!(gmi(&[]), None);
+    }
+
+    #[test]
+    fn test_mage() {
+        let values = [100, 160, 100, 180, 170, 175, 120];
+        let readings: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| reading_at(*value, i as i64 * 5 * 60_000))
+            .collect();
+
+        // SD ~= 36.1, so the 10 and 5 mg/dL wiggles don't split the last
+        // fall from 180 to 120: (60 + 60 + 80 + 60) / 4
+        assert!((mage(&readings).unwrap() - 65.0).abs() < 0.001);
+
+        // The first excursion starts from the nadir before the rise.
+        let dipped: Vec<_> = [110, 100, 160, 100, 160]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| reading_at(*value, i as i64 * 5 * 60_000))
+            .collect();
+        assert!((mage(&dipped).unwrap() - 60.0).abs() < 0.001);
+
+        assert_eq!(mage(&readings[..2]), None);
+        assert_eq!(mage(&[reading(100), reading(100), reading(100)]), None);
+    }
+
+    #[test]
+    fn test_has_signal_gap() {
+        let minutes = |m: i64| reading_at(120, 1699110415000 - m * 60_000);
+        let steady = [minutes(0), minutes(5), minutes(10), minutes(15)];
+        let gapped = [minutes(0), minutes(5), minutes(25), minutes(30)];
+
+        assert!(!has_signal_gap(&steady, 10));
+        assert!(has_signal_gap(&gapped, 10));
+        assert!(!has_signal_gap(&gapped, 20));
+        assert!(!has_signal_gap(&[minutes(0)], 10));
+    }
+
+    #[test]
+    fn test_bucketize() {
+        let start = 1699110300000;
+        let minutes = |value: i32, m: i64| reading_at(value, start + m * 60_000);
+        let readings = [
+            minutes(130, 16),
+            minutes(120, 5),
+            minutes(110, 1),
+            minutes(100, -1),
+        ];
+
+        let slots = bucketize(&readings, start, start + 15 * 60_000);
+        let values: Vec<_> = slots.iter().map(|slot| slot.map(|r| r.value)).collect();
+        assert_eq!(values, [Some(110), Some(120), None, Some(130)]);
+
+        assert_eq!(
+            bucketize(&[], start, start + 10 * 60_000),
+            [None, None, None]
+        );
+        assert!(bucketize(&readings, start, start - 1).is_empty());
+    }
+
+    #[test]
+    fn test_trend_agrees() {
+        let at = |value: i32, trend: Trend, minutes: i64| GlucosReading {
+            trend,
+            ..reading_at(value, minutes * 60_000)
+        };
+
+        assert_eq!(
+            trend_agrees(&[at(130, Trend::SingleUp, 5), at(120, Trend::Flat, 0)]),
+            Some(true)
+        );
+        assert_eq!(
+            trend_agrees(&[at(110, Trend::SingleUp, 5), at(120, Trend::Flat, 0)]),
+            Some(false)
+        );
+        assert_eq!(
+            trend_agrees(&[at(123, Trend::Flat, 5), at(120, Trend::Flat, 0)]),
+            Some(true)
+        );
+        assert_eq!(
+            trend_agrees(&[at(140, Trend::Flat, 5), at(120, Trend::Flat, 0)]),
+            Some(false)
+        );
+        assert_eq!(
+            trend_agrees(&[at(130, Trend::NotComputable, 5), at(120, Trend::Flat, 0)]),
+            None
+        );
+        assert_eq!(trend_agrees(&[at(130, Trend::SingleUp, 5)]), None);
+    }
+
+    #[test]
+    fn test_in_range_streak() {
+        let readings = [
+            reading(120),
+            reading(95),
+            reading(170),
+            reading(210),
+            reading(110),
+        ];
+
+        assert_eq!(in_range_streak(&readings, 70, 180), 3);
+        assert_eq!(in_range_streak(&readings[3..], 70, 180), 0);
+        assert_eq!(in_range_streak(&[], 70, 180), 0);
+    }
+
+    #[test]
+    fn test_ohlc_buckets() {
+        let values = [110, 130, 100, 120, 150, 140];
+        let mut readings: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| reading_at(*value, i as i64 * 5 * 60_000))
+            .collect();
+        // A third bucket with a gap before it.
+        readings.push(reading_at(90, 60 * 60_000));
+        readings.reverse();
+
+        assert_eq!(
+            ohlc_buckets(&readings, 15 * 60),
+            [
+                Ohlc {
+                    time: 0,
+                    open: 110,
+                    high: 130,
+                    low: 100,
+                    close: 100,
+                },
+                Ohlc {
+                    time: 15 * 60_000,
+                    open: 120,
+                    high: 150,
+                    low: 120,
+                    close: 140,
+                },
+                Ohlc {
+                    time: 60 * 60_000,
+                    open: 90,
+                    high: 90,
+                    low: 90,
+                    close: 90,
+                },
+            ]
+        );
+        assert!(ohlc_buckets(&[], 15 * 60).is_empty());
+    }
+
+    #[test]
+    fn test_fill_trends() {
+        let mut rising = [
+            reading_at(130, 10 * 60_000),
+            GlucosReading {
+                trend: Trend::None,
+                ..reading_at(120, 5 * 60_000)
+            },
+            reading_at(110, 0),
+        ];
+        fill_trends(&mut rising);
+        assert_eq!(rising[1].trend, Trend::SingleUp);
+        assert_eq!(rising[1].source, Source::Computed);
+        assert_eq!(rising[0].source, Source::DexcomShare);
+
+        let mut steady = [
+            reading_at(121, 10 * 60_000),
+            GlucosReading {
+                trend: Trend::None,
+                ..reading_at(120, 5 * 60_000)
+            },
+            GlucosReading {
+                trend: Trend::NotComputable,
+                ..reading_at(120, 0)
+            },
+        ];
+        fill_trends(&mut steady);
+        assert_eq!(steady[1].trend, Trend::Flat);
+        assert_eq!(steady[2].trend, Trend::NotComputable);
+
+        let mut single = [GlucosReading {
+            trend: Trend::None,
+            ..reading(120)
+        }];
+        fill_trends(&mut single);
+        assert_eq!(single[0].trend, Trend::None);
+    }
+
+    #[test]
+    fn test_computed_trend() {
+        let series = |step: i32| -> Vec<_> {
+            (0..6)
+                .map(|i| reading_at(120 + step * i, i as i64 * 5 * 60_000))
+                .rev()
+                .collect()
+        };
+
+        assert_eq!(computed_trend(&series(0)), Trend::Flat);
+        assert_eq!(computed_trend(&series(3)), Trend::Flat);
+        assert_eq!(computed_trend(&series(20)), Trend::DoubleUp);
+        assert_eq!(computed_trend(&series(12)), Trend::SingleUp);
+        assert_eq!(computed_trend(&series(-20)), Trend::DoubleDown);
+        assert_eq!(computed_trend(&series(-7)), Trend::FortyFiveDown);
+
+        assert_eq!(computed_trend(&series(20)[..1]), Trend::NotComputable);
+        assert_eq!(
+            computed_trend(&[reading(100), reading(150)]),
+            Trend::NotComputable
+        );
+    }
+
+    #[test]
+    fn test_empty_stats() {
+        assert_eq!(RollingStats::new().stats(), GlucoseStats::default());
+        assert_eq!(GlucoseStats::from_readings(&[]), GlucoseStats::default());
+    }
+}
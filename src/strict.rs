@@ -0,0 +1,56 @@
+use serde::de::DeserializeSeed;
+use serde::Deserialize;
+
+/// `serde_json::from_slice` that reports fields the target type ignored by
+/// their path (e.g. `0.Glucose`) instead of failing on them.
+pub(crate) fn from_slice_reporting<'de, D: Deserialize<'de>>(
+    buf: &'de [u8],
+    mut on_unknown: impl FnMut(String),
+) -> serde_json::Result<D> {
+    let mut deserializer = serde_json::Deserializer::from_slice(buf);
+    let value = serde_ignored::deserialize(&mut deserializer, |path| on_unknown(path.to_string()))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+pub(crate) fn from_slice<'de, D: Deserialize<'de>>(buf: &'de [u8]) -> serde_json::Result<D> {
+    from_slice_reporting(buf, warn_unknown)
+}
+
+pub(crate) fn from_slice_seed<'de, T: DeserializeSeed<'de>>(
+    buf: &'de [u8],
+    seed: T,
+) -> serde_json::Result<T::Value> {
+    let mut deserializer = serde_json::Deserializer::from_slice(buf);
+    let mut on_unknown = |path: serde_ignored::Path| warn_unknown(path.to_string());
+    let value = seed.deserialize(serde_ignored::Deserializer::new(
+        &mut deserializer,
+        &mut on_unknown,
+    ))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn warn_unknown(_path: String) {
+    #[cfg(feature = "log")]
+    log::warn!("unknown field {} in Dexcom response", _path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GlucosReading;
+
+    #[test]
+    fn test_unknown_fields_are_reported() {
+        let mut unknown = Vec::new();
+        let readings: Vec<GlucosReading> = from_slice_reporting(
+            br#"[{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":153,"Trend":"Flat","Glucose":{"Unit":"mg/dL"}}]"#,
+            |path| unknown.push(path),
+        )
+        .unwrap();
+
+        assert_eq!(readings[0].value, 153);
+        assert_eq!(unknown, ["0.Glucose"]);
+    }
+}
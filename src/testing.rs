@@ -0,0 +1,57 @@
+//! Fixtures shared by the unit tests.
+
+use std::io::Write;
+
+use mockall::predicate::*;
+
+use crate::client::{__mock_MockClient_Client::__post_request, HttpResponse, MockClient};
+use crate::{GlucosReading, Source, Trend};
+
+/// A `Flat` reading as Share returns it, with all three timestamps at
+/// `wall_time`.
+pub(crate) fn reading_json(value: i32, wall_time: i64) -> String {
+    format!(
+        r#"{{"WT":"Date({0})","ST":"Date({0})","DT":"Date({0})","Value":{1},"Trend":"Flat"}}"#,
+        wall_time, value
+    )
+}
+
+pub(crate) fn reading(value: i32) -> GlucosReading {
+    reading_at(value, 0)
+}
+
+pub(crate) fn reading_at(value: i32, wall_time: i64) -> GlucosReading {
+    GlucosReading {
+        value,
+        trend: Trend::Flat,
+        wall_time,
+        system_time: wall_time,
+        display_time: wall_time,
+        source: Source::DexcomShare,
+    }
+}
+
+/// Answers every request whose URI ends with `uri` with `status` and `body`.
+pub(crate) fn expect_response<'a>(
+    client: &'a mut MockClient,
+    uri: &'static str,
+    status: u16,
+    body: impl Into<String>,
+) -> &'a mut __post_request::Expectation {
+    let body = body.into();
+    client
+        .expect_post_request()
+        .with(
+            function(move |request: &str| request.ends_with(uri)),
+            always(),
+            always(),
+            always(),
+        )
+        .returning(move |_, _, _, mut buf| {
+            Ok(HttpResponse {
+                body_len: buf.write(body.as_bytes()).unwrap(),
+                status,
+                location: None,
+            })
+        })
+}
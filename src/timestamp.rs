@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/// Why a `Date(...)` timestamp failed to parse.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TimestampParseError {
+    /// The value isn't wrapped in `Date(` and `)`.
+    MissingWrapper,
+    /// Nothing between the parentheses.
+    EmptyEpoch,
+    /// The epoch milliseconds aren't an integer.
+    InvalidEpoch,
+    /// The offset after the epoch isn't `+hhmm` or `-hhmm`.
+    InvalidOffset,
+}
+
+impl fmt::Display for TimestampParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TimestampParseError::MissingWrapper => "missing Date(...) wrapper",
+            TimestampParseError::EmptyEpoch => "empty epoch",
+            TimestampParseError::InvalidEpoch => "epoch is not an integer",
+            TimestampParseError::InvalidOffset => "offset is not +hhmm or -hhmm",
+        })
+    }
+}
+
+impl std::error::Error for TimestampParseError {}
+
+/// Parses the Share API's `Date(1699110415000)` / `Date(1699110415000+0900)`
+/// format into epoch milliseconds. The epoch part is always UTC; a trailing
+/// offset only describes the local time of the device and is ignored.
+pub fn parse_date(value: &str) -> Option<i64> {
+    try_parse_date(value).ok()
+}
+
+/// `parse_date` reporting what was wrong with a malformed value.
+pub fn try_parse_date(value: &str) -> Result<i64, TimestampParseError> {
+    let inner = value
+        .strip_prefix("Date(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or(TimestampParseError::MissingWrapper)?;
+
+    let (epoch, offset) = match inner.rfind(['+', '-']) {
+        Some(0) | None => (inner, None),
+        Some(index) => (&inner[..index], Some(&inner[index + 1..])),
+    };
+
+    if let Some(offset) = offset {
+        if offset.len() != 4 || !offset.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(TimestampParseError::InvalidOffset);
+        }
+    }
+
+    if epoch.is_empty() {
+        return Err(TimestampParseError::EmptyEpoch);
+    }
+    epoch.parse().map_err(|_| TimestampParseError::InvalidEpoch)
+}
+
+// serde doesn't tell a `with` module which field it is decoding, so the
+// message quotes the offending value instead.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let value = Cow::<'de, str>::deserialize(deserializer)?;
+    try_parse_date(&value).map_err(|error| {
+        de::Error::custom(format_args!("invalid timestamp {:?}: {}", value, error))
+    })
+}
+
+pub(crate) fn serialize<S: Serializer>(epoch_ms: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("Date({})", epoch_ms))
+}
+
+/// Formats epoch milliseconds as an RFC 7231 HTTP-date,
+/// e.g. `Sat, 04 Nov 2023 15:06:55 GMT`.
+pub fn to_http_date(epoch_ms: i64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let secs = epoch_ms.div_euclid(1000);
+    let days = secs.div_euclid(86_400);
+    let secs_of_day = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+/// Formats epoch milliseconds as an ISO 8601 UTC timestamp,
+/// e.g. `2023-11-04T15:06:55.000Z`.
+pub fn to_iso8601(epoch_ms: i64) -> String {
+    let secs = epoch_ms.div_euclid(1000);
+    let secs_of_day = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        epoch_ms.rem_euclid(1000),
+    )
+}
+
+// Howard Hinnant's days-to-civil algorithm.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("Date(1699110415000)"), Some(1699110415000));
+        assert_eq!(parse_date("Date(1699110415000+0900)"), Some(1699110415000));
+        assert_eq!(parse_date("Date(1699110415000-0500)"), Some(1699110415000));
+        assert_eq!(parse_date("Date(1699110415000+09)"), None);
+        assert_eq!(parse_date("1699110415000"), None);
+    }
+
+    #[test]
+    fn test_try_parse_date_errors() {
+        use TimestampParseError::*;
+
+        assert_eq!(try_parse_date("Date()"), Err(EmptyEpoch));
+        assert_eq!(try_parse_date("Date(abc)"), Err(InvalidEpoch));
+        assert_eq!(try_parse_date("1699110415000"), Err(MissingWrapper));
+        assert_eq!(try_parse_date("Date(1699110415000"), Err(MissingWrapper));
+        assert_eq!(try_parse_date("Date(1699110415000+9)"), Err(InvalidOffset));
+
+        let error = serde_json::from_str::<crate::GlucosReading>(
+            r#"{"WT":"Date(1699110415000)","ST":"Date(abc)","DT":"Date(1699110415000)","Value":153,"Trend":"Flat"}"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with(r#"invalid timestamp "Date(abc)": epoch is not an integer"#));
+    }
+
+    #[test]
+    fn test_parse_owned_timestamps() {
+        let body = r#"{"WT":"Date(1699110415000\u002b0900)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":153,"Trend":"Flat"}"#;
+
+        let escaped = serde_json::from_str::<crate::GlucosReading>(body).unwrap();
+        assert_eq!(escaped.wall_time, 1699110415000);
+
+        let reader = serde_json::from_reader::<_, crate::GlucosReading>(body.as_bytes()).unwrap();
+        assert_eq!(reader.wall_time, 1699110415000);
+
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+        let value = serde_json::from_value::<crate::GlucosReading>(value).unwrap();
+        assert_eq!(value.wall_time, 1699110415000);
+    }
+
+    #[test]
+    fn test_to_http_date() {
+        assert_eq!(to_http_date(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(to_http_date(1699110415000), "Sat, 04 Nov 2023 15:06:55 GMT");
+    }
+
+    #[test]
+    fn test_to_iso8601() {
+        assert_eq!(to_iso8601(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(to_iso8601(1699110415123), "2023-11-04T15:06:55.123Z");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{GlucosReading, Trend};
+
+/// A step in the post-processing `Dexcom` applies to every reading returned
+/// by `get_current_glucose_reading` and `get_glucose_readings`, in the order
+/// added with `Dexcom::add_transform`. Returning `None` drops the reading.
+pub trait ReadingTransform {
+    fn apply(&self, reading: GlucosReading) -> Option<GlucosReading>;
+}
+
+impl<F: Fn(GlucosReading) -> Option<GlucosReading>> ReadingTransform for F {
+    fn apply(&self, reading: GlucosReading) -> Option<GlucosReading> {
+        self(reading)
+    }
+}
+
+/// Drops readings clamped at the sensor limits, whose value is only a bound;
+/// see `GlucosReading::is_below_range` and `is_above_range`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RejectClamped;
+
+impl ReadingTransform for RejectClamped {
+    fn apply(&self, reading: GlucosReading) -> Option<GlucosReading> {
+        (!reading.is_below_range() && !reading.is_above_range()).then_some(reading)
+    }
+}
+
+/// Replaces `Trend::None` with the given trend, e.g. `Trend::Flat` for a
+/// display that always shows an arrow, tagging Share readings
+/// `Source::Computed`. `stats::fill_trends` computes the
+/// trend from neighboring readings instead.
+#[derive(Debug, Clone, Copy)]
+pub struct FillTrend(pub Trend);
+
+impl ReadingTransform for FillTrend {
+    fn apply(&self, mut reading: GlucosReading) -> Option<GlucosReading> {
+        if reading.trend == Trend::None {
+            reading.trend = self.0;
+            reading.source = reading.source.computed();
+        }
+        Some(reading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Source, SENSOR_HIGH, SENSOR_LOW};
+
+    #[test]
+    fn test_built_in_transforms() {
+        assert_eq!(
+            RejectClamped.apply(GlucosReading::new(120, Trend::Flat)),
+            Some(GlucosReading::new(120, Trend::Flat))
+        );
+        assert_eq!(
+            RejectClamped.apply(GlucosReading::new(SENSOR_LOW, Trend::Flat)),
+            None
+        );
+        assert_eq!(
+            RejectClamped.apply(GlucosReading::new(SENSOR_HIGH, Trend::Flat)),
+            None
+        );
+
+        let fill = FillTrend(Trend::Flat);
+        let filled = fill.apply(GlucosReading::new(120, Trend::None)).unwrap();
+        assert_eq!(
+            (filled.trend, filled.source),
+            (Trend::Flat, Source::Computed)
+        );
+        let synthetic = GlucosReading::synthetic(120, Trend::None, 0);
+        assert_eq!(fill.apply(synthetic).unwrap().source, Source::Synthetic);
+        assert_eq!(
+            fill.apply(GlucosReading::new(120, Trend::NotComputable))
+                .unwrap()
+                .trend,
+            Trend::NotComputable
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Number of `Trend` variants, the length of `Trend::all`.
+pub const TREND_COUNT: usize = 11;
+
+#[repr(u8)]
+#[derive(Serialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trend {
+    None,
+    DoubleUp,
+    SingleUp,
+    FortyFiveUp,
+    Flat,
+    FortyFiveDown,
+    SingleDown,
+    DoubleDown,
+    NotComputable,
+    RateOutOfRange,
+    /// A trend name this crate doesn't know yet, so a reading with a newly
+    /// introduced trend still comes through.
+    Unknown,
+}
+
+/// Glyph set used by `Trend::render`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum TrendStyle {
+    /// Unicode arrows, as `arrow` and `Display`.
+    #[default]
+    Arrow,
+    /// Plain ASCII for terminals and small displays.
+    Ascii,
+    /// Title-case words, suitable for screen readers.
+    Word,
+    Emoji,
+}
+
+/// Everything about a trend in one serializable payload.
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+pub struct TrendDetail {
+    pub code: u8,
+    pub name: &'static str,
+    pub arrow: &'static str,
+    pub description: &'static str,
+    pub angle: Option<i16>,
+}
+
+impl Trend {
+    const ALL: [Trend; TREND_COUNT] = [
+        Trend::None,
+        Trend::DoubleUp,
+        Trend::SingleUp,
+        Trend::FortyFiveUp,
+        Trend::Flat,
+        Trend::FortyFiveDown,
+        Trend::SingleDown,
+        Trend::DoubleDown,
+        Trend::NotComputable,
+        Trend::RateOutOfRange,
+        Trend::Unknown,
+    ];
+
+    /// Every variant in `index` order, e.g. to build a translation table.
+    pub fn all() -> [Trend; TREND_COUNT] {
+        Self::ALL
+    }
+
+    /// Position in `all`, `0..TREND_COUNT`. Stable across releases; new
+    /// variants are only ever appended.
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The trend for a `#[repr(u8)]` discriminant, as some feeds send it.
+    /// Only `0..=9` are codes: no feed sends `Unknown`, which stands for
+    /// unrecognized names.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL
+            .get(code as usize)
+            .copied()
+            .filter(|trend| *trend != Trend::Unknown)
+    }
+
+    pub fn name(&self) -> &'static str {
+        use Trend::*;
+        match self {
+            None => "None",
+            DoubleUp => "DoubleUp",
+            SingleUp => "SingleUp",
+            FortyFiveUp => "FortyFiveUp",
+            Flat => "Flat",
+            FortyFiveDown => "FortyFiveDown",
+            SingleDown => "SingleDown",
+            DoubleDown => "DoubleDown",
+            NotComputable => "NotComputable",
+            RateOutOfRange => "RateOutOfRange",
+            Unknown => "Unknown",
+        }
+    }
+
+    /// The `direction` string Nightscout uses. It matches `name` except for
+    /// the sentinels, which Nightscout spells in uppercase with spaces.
+    pub fn nightscout_direction(&self) -> &'static str {
+        use Trend::*;
+        match self {
+            None | Unknown => "NONE",
+            NotComputable => "NOT COMPUTABLE",
+            RateOutOfRange => "RATE OUT OF RANGE",
+            trend => trend.name(),
+        }
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        use Trend::*;
+        match self {
+            None => "",
+            DoubleUp => "↑↑",
+            SingleUp => "↑",
+            FortyFiveUp => "↗",
+            Flat => "→",
+            FortyFiveDown => "↘",
+            SingleDown => "↓",
+            DoubleDown => "↓↓",
+            NotComputable => "?",
+            RateOutOfRange => "-",
+            Unknown => "??",
+        }
+    }
+
+    pub fn render(&self, style: TrendStyle) -> &'static str {
+        use Trend::*;
+        match style {
+            TrendStyle::Arrow => self.arrow(),
+            TrendStyle::Ascii => match self {
+                None => "",
+                DoubleUp => "^^",
+                SingleUp => "^",
+                FortyFiveUp => "/",
+                Flat => "->",
+                FortyFiveDown => "\\",
+                SingleDown => "v",
+                DoubleDown => "vv",
+                NotComputable => "?",
+                RateOutOfRange => "-",
+                Unknown => "??",
+            },
+            TrendStyle::Word => match self {
+                None => "No Trend",
+                DoubleUp => "Rising Fast",
+                SingleUp => "Rising",
+                FortyFiveUp => "Rising Slowly",
+                Flat => "Steady",
+                FortyFiveDown => "Falling Slowly",
+                SingleDown => "Falling",
+                DoubleDown => "Falling Fast",
+                NotComputable => "Trend Unknown",
+                RateOutOfRange => "Rate Out of Range",
+                Unknown => "Unrecognized Trend",
+            },
+            TrendStyle::Emoji => match self {
+                None => "",
+                DoubleUp => "⏫",
+                SingleUp => "⬆️",
+                FortyFiveUp => "↗️",
+                Flat => "➡️",
+                FortyFiveDown => "↘️",
+                SingleDown => "⬇️",
+                DoubleDown => "⏬",
+                NotComputable => "❓",
+                RateOutOfRange => "⚠️",
+                Unknown => "❔",
+            },
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        use Trend::*;
+        match self {
+            None => "",
+            DoubleUp => "rising quickly",
+            SingleUp => "rising",
+            FortyFiveUp => "rising slightly",
+            Flat => "steady",
+            FortyFiveDown => "falling slightly",
+            SingleDown => "falling",
+            DoubleDown => "falling quickly",
+            NotComputable => "unable to determine trend",
+            RateOutOfRange => "trend unavailable",
+            Unknown => "unrecognized trend",
+        }
+    }
+
+    pub fn detail(&self) -> TrendDetail {
+        TrendDetail {
+            code: *self as u8,
+            name: self.name(),
+            arrow: self.arrow(),
+            description: self.description(),
+            angle: self.angle_degrees(),
+        }
+    }
+
+    /// Urgency score for prioritizing alerts, from 0 (Flat) to 6 (DoubleDown).
+    ///
+    /// Faster changes score higher, and a fall scores just above a rise of the
+    /// same speed. The sentinels (None, NotComputable, RateOutOfRange,
+    /// Unknown) score 1: the direction is unknown, which deserves more
+    /// attention than Flat but less than a known change.
+    pub fn severity(&self) -> u8 {
+        use Trend::*;
+        match self {
+            Flat => 0,
+            None | NotComputable | RateOutOfRange | Unknown => 1,
+            FortyFiveUp => 1,
+            FortyFiveDown => 2,
+            SingleUp => 3,
+            SingleDown => 4,
+            DoubleUp => 5,
+            DoubleDown => 6,
+        }
+    }
+
+    /// Representative rate of change in mg/dL per minute: the middle of each
+    /// arrow's band (Flat is within ±1, FortyFive 1–2, Single 2–3, Double >3).
+    pub fn rate_per_minute(&self) -> Option<f32> {
+        use Trend::*;
+        match self {
+            DoubleUp => Some(3.5),
+            SingleUp => Some(2.5),
+            FortyFiveUp => Some(1.5),
+            Flat => Some(0.0),
+            FortyFiveDown => Some(-1.5),
+            SingleDown => Some(-2.5),
+            DoubleDown => Some(-3.5),
+            None | NotComputable | RateOutOfRange | Unknown => Option::None,
+        }
+    }
+
+    /// Needle angle for a gauge, counter-clockwise from horizontal: `Flat` is
+    /// 0, the `FortyFive` arrows ±45 and both `Single` and `Double` ±90, since
+    /// a needle can't point past vertical. `None` for the sentinels that carry
+    /// no direction.
+    pub fn angle_degrees(&self) -> Option<i16> {
+        use Trend::*;
+        match self {
+            DoubleUp | SingleUp => Some(90),
+            FortyFiveUp => Some(45),
+            Flat => Some(0),
+            FortyFiveDown => Some(-45),
+            SingleDown | DoubleDown => Some(-90),
+            None | NotComputable | RateOutOfRange | Unknown => Option::None,
+        }
+    }
+}
+
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.arrow())
+    }
+}
+
+// Accepts the PascalCase name or the numeric code. Unknown names decode as
+// `Trend::Unknown`; out-of-range codes are errors.
+impl<'de> Deserialize<'de> for Trend {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TrendVisitor;
+
+        impl de::Visitor<'_> for TrendVisitor {
+            type Value = Trend;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a trend name or a code from 0 to 9")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Trend, E> {
+                Ok(Trend::ALL
+                    .into_iter()
+                    .find(|trend| trend.name() == value)
+                    .unwrap_or(Trend::Unknown))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Trend, E> {
+                u8::try_from(value)
+                    .ok()
+                    .and_then(Trend::from_code)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Trend, E> {
+                u64::try_from(value)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+                    .and_then(|value| self.visit_u64(value))
+            }
+        }
+
+        deserializer.deserialize_any(TrendVisitor)
+    }
+}
+
+/// Serializes a `Trend` as its `arrow` glyph instead of the PascalCase name,
+/// for use with `#[serde(with = "dexcom::trend::as_arrow")]`.
+pub mod as_arrow {
+    use std::borrow::Cow;
+
+    use super::Trend;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(trend: &Trend, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(trend.arrow())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Trend, D::Error> {
+        let arrow = Cow::<'de, str>::deserialize(deserializer)?;
+        Trend::ALL
+            .into_iter()
+            .find(|trend| trend.arrow() == arrow)
+            .ok_or_else(|| de::Error::custom(format_args!("unknown trend arrow {:?}", arrow)))
+    }
+}
+
+/// Orders by `severity`, breaking ties by discriminant so that `Ord`
+/// stays consistent with `Eq`.
+impl Ord for Trend {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.severity(), *self as u8).cmp(&(other.severity(), *other as u8))
+    }
+}
+
+impl PartialOrd for Trend {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trend_detail() {
+        assert_eq!(
+            Trend::Flat.detail(),
+            TrendDetail {
+                code: 4,
+                name: "Flat",
+                arrow: "→",
+                description: "steady",
+                angle: Some(0),
+            }
+        );
+
+        assert_eq!(
+            Trend::NotComputable.detail(),
+            TrendDetail {
+                code: 8,
+                name: "NotComputable",
+                arrow: "?",
+                description: "unable to determine trend",
+                angle: None,
+            }
+        );
+
+        assert_eq!(
+            serde_json::to_string(&Trend::DoubleDown.detail()).unwrap(),
+            r#"{"code":7,"name":"DoubleDown","arrow":"↓↓","description":"falling quickly","angle":-90}"#
+        );
+    }
+
+    #[test]
+    fn test_nightscout_direction() {
+        let directions = Trend::ALL.map(|trend| trend.nightscout_direction());
+        assert_eq!(
+            directions,
+            [
+                "NONE",
+                "DoubleUp",
+                "SingleUp",
+                "FortyFiveUp",
+                "Flat",
+                "FortyFiveDown",
+                "SingleDown",
+                "DoubleDown",
+                "NOT COMPUTABLE",
+                "RATE OUT OF RANGE",
+                "NONE",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_as_arrow_round_trip() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Payload {
+            #[serde(with = "as_arrow")]
+            trend: Trend,
+        }
+
+        for trend in Trend::ALL {
+            let json = serde_json::to_string(&Payload { trend }).unwrap();
+            assert_eq!(json, format!(r#"{{"trend":"{}"}}"#, trend.arrow()));
+            assert_eq!(
+                serde_json::from_str::<Payload>(&json).unwrap(),
+                Payload { trend }
+            );
+        }
+
+        assert_eq!(
+            serde_json::from_str::<Payload>(r#"{"trend":"\u2192"}"#).unwrap(),
+            Payload { trend: Trend::Flat }
+        );
+        assert_eq!(
+            serde_json::from_value::<Payload>(serde_json::json!({ "trend": "→" })).unwrap(),
+            Payload { trend: Trend::Flat }
+        );
+
+        assert!(serde_json::from_str::<Payload>(r#"{"trend":"Flat"}"#).is_err());
+    }
+
+    #[test]
+    fn test_all_and_index() {
+        let all = Trend::all();
+
+        assert_eq!(all.len(), TREND_COUNT);
+        for (index, trend) in all.iter().enumerate() {
+            assert_eq!(trend.index(), index);
+        }
+    }
+
+    #[test]
+    fn test_angle_degrees() {
+        use Trend::*;
+
+        assert_eq!(
+            [
+                DoubleUp,
+                SingleUp,
+                FortyFiveUp,
+                Flat,
+                FortyFiveDown,
+                SingleDown,
+                DoubleDown
+            ]
+            .map(|trend| trend.angle_degrees()),
+            [90, 90, 45, 0, -45, -90, -90].map(Some)
+        );
+        for trend in [None, NotComputable, RateOutOfRange] {
+            assert_eq!(trend.angle_degrees(), Option::None);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            Trend::ALL.map(|trend| trend.render(TrendStyle::Ascii)),
+            ["", "^^", "^", "/", "->", "\\", "v", "vv", "?", "-", "??"]
+        );
+        assert_eq!(
+            Trend::ALL.map(|trend| trend.render(TrendStyle::Word)),
+            [
+                "No Trend",
+                "Rising Fast",
+                "Rising",
+                "Rising Slowly",
+                "Steady",
+                "Falling Slowly",
+                "Falling",
+                "Falling Fast",
+                "Trend Unknown",
+                "Rate Out of Range",
+                "Unrecognized Trend",
+            ]
+        );
+        for trend in Trend::ALL {
+            assert_eq!(trend.render(TrendStyle::default()), trend.to_string());
+            assert!(trend
+                .render(TrendStyle::Word)
+                .chars()
+                .all(|c| c.is_ascii_alphabetic() || c == ' '));
+        }
+    }
+
+    #[test]
+    fn test_severity() {
+        assert!(Trend::DoubleDown.severity() > Trend::Flat.severity());
+        assert!(Trend::DoubleUp.severity() > Trend::FortyFiveDown.severity());
+        assert!(Trend::DoubleDown > Trend::DoubleUp);
+        assert!(Trend::NotComputable > Trend::Flat);
+
+        let mut trends = [
+            Trend::Flat,
+            Trend::DoubleDown,
+            Trend::SingleUp,
+            Trend::FortyFiveDown,
+        ];
+        trends.sort();
+        assert_eq!(
+            trends,
+            [
+                Trend::Flat,
+                Trend::FortyFiveDown,
+                Trend::SingleUp,
+                Trend::DoubleDown
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_name_or_code() {
+        assert_eq!(serde_json::from_str::<Trend>("4").unwrap(), Trend::Flat);
+        assert_eq!(
+            serde_json::from_str::<Trend>(r#""Flat""#).unwrap(),
+            Trend::Flat
+        );
+        assert_eq!(serde_json::from_str::<Trend>("0").unwrap(), Trend::None);
+        assert!(serde_json::from_str::<Trend>("99").is_err());
+        assert!(serde_json::from_str::<Trend>("10").is_err());
+        assert_eq!(Trend::from_code(9), Some(Trend::RateOutOfRange));
+        assert_eq!(Trend::from_code(10), None);
+        assert!(serde_json::from_str::<Trend>("-1").is_err());
+        assert_eq!(
+            serde_json::from_str::<Trend>(r#""Sideways""#).unwrap(),
+            Trend::Unknown
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum GlucoseUnit {
+    #[default]
+    MgDl,
+    MmolL,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseGlucoseUnitError(pub String);
+
+impl GlucoseUnit {
+    /// mmol/L per mg/dL.
+    pub const MMOL_L_PER_MG_DL: f32 = 0.0555;
+
+    /// Converts a mg/dL value into this unit.
+    pub fn from_mg_dl(self, value: i32) -> f32 {
+        match self {
+            GlucoseUnit::MgDl => value as f32,
+            GlucoseUnit::MmolL => value as f32 * Self::MMOL_L_PER_MG_DL,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            GlucoseUnit::MgDl => "mg/dL",
+            GlucoseUnit::MmolL => "mmol/L",
+        }
+    }
+}
+
+impl fmt::Display for GlucoseUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for GlucoseUnit {
+    type Err = ParseGlucoseUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [GlucoseUnit::MgDl, GlucoseUnit::MmolL]
+            .into_iter()
+            .find(|unit| unit.symbol().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseGlucoseUnitError(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("mg/dL".parse(), Ok(GlucoseUnit::MgDl));
+        assert_eq!("MMOL/L".parse(), Ok(GlucoseUnit::MmolL));
+        assert_eq!(
+            "mmol".parse::<GlucoseUnit>(),
+            Err(ParseGlucoseUnitError("mmol".to_owned()))
+        );
+
+        for unit in [GlucoseUnit::MgDl, GlucoseUnit::MmolL] {
+            assert_eq!(unit.to_string().parse(), Ok(unit));
+        }
+    }
+
+    #[test]
+    fn test_from_mg_dl() {
+        assert_eq!(GlucoseUnit::MgDl.from_mg_dl(153), 153.0);
+        assert!((GlucoseUnit::MmolL.from_mg_dl(180) - 9.99).abs() < 1e-4);
+    }
+}
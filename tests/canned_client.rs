@@ -0,0 +1,104 @@
+#![cfg(feature = "test-util")]
+
+use dexcom::client::CannedClient;
+use dexcom::{ClientError, Dexcom, DexcomError, Trend};
+
+#[test]
+fn login_and_read() {
+    let mut client = CannedClient::new()
+        .with_response(
+            "/General/AuthenticatePublisherAccount",
+            200,
+            r#""0b3f48a6-4a7c-4d0a-9e1a-fa5e0b4e1a6d""#,
+        )
+        .with_response(
+            "/General/LoginPublisherAccountById",
+            200,
+            r#""6e2d9a47-35b1-4f6a-8c1e-2b7d3c9ef0a4""#,
+        )
+        .with_response(
+            "/Publisher/ReadPublisherLatestGlucoseValues",
+            200,
+            r#"[{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000+0900)","Value":153,"Trend":"Flat"}]"#,
+        );
+
+    let mut dexcom = Dexcom::new(&mut client);
+    let session_id = dexcom.load_session_id("user", "pass", "app").unwrap();
+    assert_eq!(session_id, "6e2d9a47-35b1-4f6a-8c1e-2b7d3c9ef0a4");
+
+    let reading = dexcom.get_current_glucose_reading(&session_id).unwrap();
+    assert_eq!(reading.value, 153);
+    assert_eq!(reading.trend, Trend::Flat);
+
+    drop(dexcom);
+    assert_eq!(client.requests().len(), 3);
+}
+
+#[test]
+fn invalid_password() {
+    let mut client = CannedClient::new().with_response(
+        "/General/AuthenticatePublisherAccount",
+        500,
+        r#"{"Code":"AccountPasswordInvalid"}"#,
+    );
+
+    let mut dexcom = Dexcom::new(&mut client);
+    assert!(matches!(
+        dexcom.load_session_id("user", "wrong", "app"),
+        Err(ClientError::DexcomError(
+            DexcomError::AccountPasswordInvalid,
+            Some(500)
+        ))
+    ));
+}
+
+#[test]
+fn call_custom_endpoint() {
+    #[derive(serde::Serialize)]
+    struct DeviceInfoRequest<'a> {
+        #[serde(rename = "sessionId")]
+        session_id: &'a str,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct DeviceInfo {
+        #[serde(rename = "TransmitterId")]
+        transmitter_id: String,
+    }
+
+    let mut client = CannedClient::new()
+        .with_response(
+            "/Publisher/ReadPublisherDeviceInfo",
+            200,
+            r#"{"TransmitterId":"8G1234"}"#,
+        )
+        .with_response(
+            "/Publisher/ReadPublisherMissing",
+            500,
+            r#"{"Code":"SessionNotValid"}"#,
+        );
+
+    let mut dexcom = Dexcom::new(&mut client);
+    let request = DeviceInfoRequest {
+        session_id: "a21d18db-a276-40bc-8337-77dcd02df53e",
+    };
+
+    let info: DeviceInfo = dexcom
+        .call(
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherDeviceInfo",
+            &request,
+        )
+        .unwrap();
+    assert_eq!(info.transmitter_id, "8G1234");
+
+    assert!(matches!(
+        dexcom.call::<_, DeviceInfo>(
+            "https://share2.dexcom.com/ShareWebServices/Services/Publisher/ReadPublisherMissing",
+            &request,
+        ),
+        Err(ClientError::DexcomError(
+            DexcomError::SessionInvalid,
+            Some(500)
+        ))
+    ));
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "test-util")]
+
+use dexcom::client::ReplayClient;
+use dexcom::{Dexcom, Trend};
+
+#[test]
+fn replay_cycles_through_readings() {
+    let mut client = ReplayClient::new([
+        r#"[{"WT":"Date(1699110415000)","ST":"Date(1699110415000)","DT":"Date(1699110415000)","Value":120,"Trend":"Flat"}]"#,
+        r#"[{"WT":"Date(1699110715000)","ST":"Date(1699110715000)","DT":"Date(1699110715000)","Value":131,"Trend":"FortyFiveUp"}]"#,
+        r#"[{"WT":"Date(1699111015000)","ST":"Date(1699111015000)","DT":"Date(1699111015000)","Value":145,"Trend":"SingleUp"}]"#,
+    ]);
+    let mut dexcom = Dexcom::new(&mut client);
+    let session_id = dexcom.load_session_id("user", "pass", "app").unwrap();
+
+    let readings = (0..5)
+        .map(|_| {
+            let reading = dexcom.get_current_glucose_reading(&session_id).unwrap();
+            (reading.value, reading.trend)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        readings,
+        [
+            (120, Trend::Flat),
+            (131, Trend::FortyFiveUp),
+            (145, Trend::SingleUp),
+            (120, Trend::Flat),
+            (131, Trend::FortyFiveUp),
+        ]
+    );
+}
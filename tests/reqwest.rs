@@ -0,0 +1,18 @@
+#![cfg(all(feature = "reqwest", feature = "network-tests"))]
+
+use dexcom::client::ReqwestClient;
+use dexcom::{ClientError, Dexcom};
+
+#[test]
+fn test_load_session_id_with_invalid_credentials() {
+    let mut client = ReqwestClient::new();
+    let mut dexcom = Dexcom::new(&mut client);
+
+    let result = dexcom.load_session_id("rsdexcom-invalid-user", "invalid", "invalid");
+
+    assert!(
+        matches!(result, Err(ClientError::DexcomError(..))),
+        "{:?}",
+        result
+    );
+}